@@ -5,7 +5,7 @@ use env_logger;
 use log::{error, info};
 
 use xplane_udp::auto_discover::AutoDiscover;
-use xplane_udp::dataref_type::{DataRefType, DataRefValueType};
+use xplane_udp::dataref_type::DataRefType;
 use xplane_udp::session::Session;
 
 #[tokio::main]
@@ -58,18 +58,18 @@ async fn main() -> io::Result<()> {
     let loop_count = 10;
 
     for _ in 0..loop_count {
-        let num_engines = session.get_dataref("sim/aircraft/engine/acf_num_engines");
+        let num_engines = session.get::<i32>("sim/aircraft/engine/acf_num_engines");
         match num_engines {
-            Some(DataRefValueType::Int(num_engines)) => {
+            Ok(num_engines) => {
                 info!("Number of engines: {}", num_engines);
             }
-            _ => {
-                error!("Failed to get number of engines");
+            Err(e) => {
+                error!("Failed to get number of engines: {}", e);
             }
         }
-        let dome = session.get_dataref("laminar/B738/toggle_switch/cockpit_dome_pos");
+        let dome = session.get::<i32>("laminar/B738/toggle_switch/cockpit_dome_pos");
         match dome {
-            Some(DataRefValueType::Int(dome)) => {
+            Ok(dome) => {
                 match dome {
                     -1 => {
                         session.cmd("laminar/B738/toggle_switch/cockpit_dome_up").await?;
@@ -88,8 +88,8 @@ async fn main() -> io::Result<()> {
                     }
                 }
             }
-            _ => {
-                error!("Failed to get dome position");
+            Err(e) => {
+                error!("Failed to get dome position: {}", e);
             }
         }
         sleep(std::time::Duration::from_secs(1));
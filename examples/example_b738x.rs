@@ -4,11 +4,10 @@ use std::thread::sleep;
 use env_logger;
 use log::{error, info};
 
-use xplane_udp::dataref_type::{DataRefType, DataRefValueType};
-use xplane_udp::session::Session;
+use xplane_udp::blocking::Session;
+use xplane_udp::dataref_type::DataRefType;
 
-#[tokio::main]
-async fn main() -> io::Result<()>  {
+fn main() -> io::Result<()>  {
     env_logger::init();
     
     let session = Session::manual(
@@ -16,7 +15,7 @@ async fn main() -> io::Result<()>  {
         SocketAddr::from(([10, 0, 0, 10], 49001)),
     );
 
-    let mut session = match session.await {
+    let mut session = match session {
         Ok(session) => {
             session
         }
@@ -26,9 +25,9 @@ async fn main() -> io::Result<()>  {
         }
     };
 
-    session.run().await?;
+    session.run()?;
 
-    match session.subscribe("sim/aircraft/engine/acf_num_engines", 1, DataRefType::Int).await {
+    match session.subscribe("sim/aircraft/engine/acf_num_engines", 1, DataRefType::Int) {
         Ok(_) => {
             info!("Subscribed to sim/aircraft/engine/acf_num_engines");
         }
@@ -37,7 +36,7 @@ async fn main() -> io::Result<()>  {
         }
     }
 
-    match session.subscribe("laminar/B738/toggle_switch/cockpit_dome_pos", 1, DataRefType::Int).await {
+    match session.subscribe("laminar/B738/toggle_switch/cockpit_dome_pos", 1, DataRefType::Int) {
         Ok(_) => {
             info!("Subscribed to laminar/B738/toggle_switch/cockpit_dome_pos");
         }
@@ -49,29 +48,29 @@ async fn main() -> io::Result<()>  {
     let loop_count = 10;
 
     for _ in 0..loop_count {
-        let num_engines = session.get_dataref("sim/aircraft/engine/acf_num_engines");
+        let num_engines = session.get::<i32>("sim/aircraft/engine/acf_num_engines");
         match num_engines {
-            Some(DataRefValueType::Int(num_engines)) => {
+            Ok(num_engines) => {
                 info!("Number of engines: {}", num_engines);
             }
-            _ => {
-                error!("Failed to get number of engines");
+            Err(e) => {
+                error!("Failed to get number of engines: {}", e);
             }
         }
-        let dome = session.get_dataref("laminar/B738/toggle_switch/cockpit_dome_pos");
+        let dome = session.get::<i32>("laminar/B738/toggle_switch/cockpit_dome_pos");
         match dome {
-            Some(DataRefValueType::Int(dome)) => {
+            Ok(dome) => {
                 match dome {
                     -1 => {
-                        session.cmd("laminar/B738/toggle_switch/cockpit_dome_up").await?;
+                        session.cmd("laminar/B738/toggle_switch/cockpit_dome_up")?;
                         info!("Dome: {:?}", dome);
                     }
                     0 => {
-                        session.cmd("laminar/B738/toggle_switch/cockpit_dome_up").await?;
+                        session.cmd("laminar/B738/toggle_switch/cockpit_dome_up")?;
                         info!("Dome: {:?}", dome);
                     }
                     1 => {
-                        session.cmd("laminar/B738/toggle_switch/cockpit_dome_dn").await?;
+                        session.cmd("laminar/B738/toggle_switch/cockpit_dome_dn")?;
                         info!("Dome: {:?}", dome);
                     }
                     _ => {
@@ -79,14 +78,14 @@ async fn main() -> io::Result<()>  {
                     }
                 }
             }
-            _ => {
-                error!("Failed to get dome position");
+            Err(e) => {
+                error!("Failed to get dome position: {}", e);
             }
         }
         sleep(std::time::Duration::from_secs(1));
     }
 
     info!("Shutting down");
-    session.shutdown().await;
+    session.shutdown();
     Ok(())
 }
@@ -8,12 +8,14 @@ use std::time::Duration;
 use crossterm::event;
 use crossterm::event::{poll, KeyCode};
 use ratatui::{init, restore};
+use log::warn;
 
 use xplane_udp::command_handler::AlertMessage;
+use xplane_udp::dataref_type::DataRefValueType;
+use xplane_udp::profile::{AircraftProfile, DisplayStyle, ProfileField};
 use xplane_udp::session::Session;
 
-mod consts_b738x;
-use crate::consts_b738x::DisplayType::{Digits, Gb};
+const PROFILE_PATH: &str = "examples/profiles/b738x.toml";
 
 fn button_pg(label: &str, state: bool) -> Paragraph {
     let bg_color = if state { Color::Green } else { Color::Red };
@@ -30,7 +32,30 @@ fn display_pg(value: &str) -> Paragraph {
     ]).alignment(Alignment::Center).block(Block::default().borders(Borders::NONE))
 }
 
-async fn execute_command(command: &str, session: &Session) -> io::Result<bool> {
+/// Fields of the profile shown in the row `group`
+fn group_fields<'a>(profile: &'a AircraftProfile, group: &str) -> Vec<&'a ProfileField> {
+    profile.get_fields().iter().filter(|f| f.get_display().get_group() == Some(group)).collect()
+}
+
+/// Newest value of `field`, formatted by its display format or shown as a light
+fn render_field(f: &mut Frame, area: Rect, field: &ProfileField, session: &Session) {
+    match field.get_display().get_style() {
+        DisplayStyle::Value => {
+            let value = session.get_dataref(field.get_dataref()).unwrap_or(DataRefValueType::Unknown);
+            f.render_widget(display_pg(&field.format_value(&value)), area);
+        }
+        DisplayStyle::Indicator => {
+            let state = session.get::<bool>(field.get_dataref()).unwrap_or(false);
+            f.render_widget(button_pg(field.get_name(), state), area);
+        }
+    }
+}
+
+fn field_constraints(fields: &[&ProfileField]) -> Vec<Constraint> {
+    fields.iter().map(|f| Constraint::Length(f.get_display().get_width().unwrap_or(10))).collect()
+}
+
+async fn execute_command(command: &str, session: &Session, profile: &AircraftProfile) -> io::Result<bool> {
     let parts: Vec<&str> = command.split_whitespace().collect();
     let cmd = parts[0];
 
@@ -50,6 +75,8 @@ async fn execute_command(command: &str, session: &Session) -> io::Result<bool> {
             return Ok(false);
         }
         session.cmd(parts[1]).await?;
+    } else if cmd == "action" {
+        session.run_action(profile, &parts[1..].join(" ")).await?;
     }
 
     Ok(false)
@@ -58,6 +85,8 @@ async fn execute_command(command: &str, session: &Session) -> io::Result<bool> {
 
 #[tokio::main]
 async fn main() -> io::Result<()> {
+    env_logger::init();
+
     let mut session = Session::manual(
         SocketAddr::from(([10, 0, 0, 10], 49000)),
         SocketAddr::from(([10, 0, 0, 10], 49001)),
@@ -65,14 +94,15 @@ async fn main() -> io::Result<()> {
 
     session.run().await?;
 
-    // Subscribe to datarefs in A and B fields
-    for field in consts_b738x::MCP_A_FIELDS.iter().chain(consts_b738x::MCP_B_FIELDS.iter()) {
-        let dr_type = match field.field_type {
-            Digits => xplane_udp::dataref_type::DataRefType::Int,
-            Gb => xplane_udp::dataref_type::DataRefType::Int,
-        };
-        session.subscribe(&field.dataref, 5, dr_type).await?;
+    // Subscribe to all datarefs of the profile, fields X-Plane does not know stay empty
+    let profile = AircraftProfile::load(PROFILE_PATH)?;
+    for result in session.subscribe_profile(&profile).await {
+        if let Err(e) = result.get_result() {
+            warn!("Failed to subscribe dataref {}: {}", result.get_dataref(), e);
+        }
     }
+    let a_fields = group_fields(&profile, "MCP A");
+    let b_fields = group_fields(&profile, "MCP B");
 
     let mut command_buffer = String::new();
     let mut terminal = init();
@@ -83,7 +113,7 @@ async fn main() -> io::Result<()> {
 
             // Outer block with title and border
             let outer_block = Block::default()
-                .title("Boeing 737 NG MCP")
+                .title(profile.get_name())
                 .borders(Borders::ALL);
             f.render_widget(&outer_block, size);
 
@@ -101,41 +131,23 @@ async fn main() -> io::Result<()> {
             // Top row layout (A fields)
             let a_blocks = Layout::default()
                 .direction(Direction::Horizontal)
-                .constraints(consts_b738x::MCP_A_FIELDS.iter().map(|f| f.constraint).collect::<Vec<Constraint>>())
+                .constraints(field_constraints(&a_fields))
                 .split(main_layout[0]);
 
             // Second row layout (B fields)
             let b_blocks = Layout::default()
                 .direction(Direction::Horizontal)
-                .constraints(consts_b738x::MCP_B_FIELDS.iter().map(|f| f.constraint).collect::<Vec<Constraint>>())
+                .constraints(field_constraints(&b_fields))
                 .split(main_layout[1]);
 
             // Render A fields
-            for (block, field) in a_blocks.iter().zip(&consts_b738x::MCP_A_FIELDS) {
-                let newest_value = match session.get_dataref(&field.dataref) {
-                    Some(xplane_udp::dataref_type::DataRefValueType::Int(v)) => v,
-                    _ => 0,
-                };
-
-                let display = &field.field_type;
-                match display {
-                    Digits => f.render_widget(display_pg(&newest_value.to_string()), *block),
-                    Gb => f.render_widget(button_pg(field.name, newest_value == 1), *block),
-                }
+            for (block, field) in a_blocks.iter().zip(&a_fields) {
+                render_field(f, *block, field, &session);
             }
 
             // Render B fields
-            for (block, field) in b_blocks.iter().zip(&consts_b738x::MCP_B_FIELDS) {
-                let newest_value = match session.get_dataref(&field.dataref) {
-                    Some(xplane_udp::dataref_type::DataRefValueType::Int(v)) => v,
-                    _ => 0,
-                };
-
-                let display = &field.field_type;
-                match display {
-                    Digits => f.render_widget(display_pg(&newest_value.to_string()), *block),
-                    Gb => f.render_widget(button_pg(field.name, newest_value == 1), *block),
-                }
+            for (block, field) in b_blocks.iter().zip(&b_fields) {
+                render_field(f, *block, field, &session);
             }
 
             // Command box
@@ -161,7 +173,7 @@ async fn main() -> io::Result<()> {
                     }
                     KeyCode::Enter => {
                         // Execute the command and clear the buffer
-                        match execute_command(&command_buffer, &session).await {
+                        match execute_command(&command_buffer, &session, &profile).await {
                             Ok(true) => break,
                             _ => {}
                         }
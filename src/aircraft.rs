@@ -0,0 +1,361 @@
+//! Detection of the loaded aircraft and switching between [`AircraftProfile`]s.
+//!
+//! X-Plane publishes the aircraft identity as string datarefs, which RREF can only read
+//! byte by byte, so [`AircraftSwitcher`] subscribes every byte of the ICAO code and of the
+//! `.acf` path. A profile applies to an aircraft if one of its `aircraft` patterns equals the
+//! ICAO code or is part of the path, ignoring case. Profiles registered first win, so specific
+//! ones, e.g. `B737-800X` for the Zibo, go before generic ones such as `B738`.
+//!
+//! The switcher also keeps an [`AliasRegistry`] resolving aliases for the active profile.
+//!
+//! The bytes may arrive spread over several RREF messages, so a new identity only counts once
+//! it stayed the same for [`XP_AIRCRAFT_SETTLE_MS`].
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+
+use crate::alias::AliasRegistry;
+use crate::backend::Backend;
+use crate::consts::{XP_AIRCRAFT_FREQUENCY, XP_AIRCRAFT_ICAO_DATAREF, XP_AIRCRAFT_ICAO_LEN,
+                    XP_AIRCRAFT_PATH_DATAREF, XP_AIRCRAFT_PATH_LEN, XP_AIRCRAFT_SETTLE_MS};
+use crate::dataref::SubscriptionId;
+use crate::dataref_handler::BatchResult;
+use crate::dataref_type::{DataRefType, DataRefValueType};
+use crate::profile::AircraftProfile;
+use crate::session::Session;
+use crate::transport::Transport;
+
+/// Loaded aircraft as reported by X-Plane
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AircraftIdentity {
+    icao: String,
+    path: String,
+}
+
+impl AircraftIdentity {
+    pub fn new(icao: &str, path: &str) -> Self {
+        AircraftIdentity { icao: icao.to_string(), path: path.to_string() }
+    }
+
+    /// Decodes the byte values of the ICAO code followed by those of the path,
+    /// `None` until every byte has arrived
+    fn from_values(values: &[DataRefValueType]) -> Option<Self> {
+        let (icao, path) = values.split_at(XP_AIRCRAFT_ICAO_LEN.min(values.len()));
+        Some(AircraftIdentity { icao: decode_string(icao)?, path: decode_string(path)? })
+    }
+
+    pub fn get_icao(&self) -> &str { &self.icao }
+    /// `.acf` path relative to the X-Plane folder, truncated to [`XP_AIRCRAFT_PATH_LEN`] bytes
+    pub fn get_path(&self) -> &str { &self.path }
+
+    /// Whether `pattern` equals the ICAO code or is part of the path, ignoring case
+    pub fn matches(&self, pattern: &str) -> bool {
+        self.icao.eq_ignore_ascii_case(pattern)
+            || self.path.to_lowercase().contains(&pattern.to_lowercase())
+    }
+}
+
+/// NUL-terminated string from consecutive byte values
+fn decode_string(values: &[DataRefValueType]) -> Option<String> {
+    let mut string = String::new();
+    for value in values {
+        match *value {
+            DataRefValueType::Byte(0) => break,
+            DataRefValueType::Byte(b) => string.push(b as char),
+            _ => return None,
+        }
+    }
+    Some(string)
+}
+
+/// Emitted by [`AircraftSwitcher`] when a different aircraft was loaded
+#[derive(Debug)]
+pub struct AircraftChanged {
+    previous: Option<AircraftIdentity>,
+    current: AircraftIdentity,
+    profile: Option<String>,
+    subscriptions: Vec<BatchResult<SubscriptionId>>,
+}
+
+impl AircraftChanged {
+    /// `None` for the first aircraft detected
+    pub fn get_previous(&self) -> Option<&AircraftIdentity> { self.previous.as_ref() }
+    pub fn get_current(&self) -> &AircraftIdentity { &self.current }
+    /// Name of the profile now active, `None` if no registered profile matches
+    pub fn get_profile(&self) -> Option<&str> { self.profile.as_deref() }
+    /// Results of subscribing the fields of the new profile
+    pub fn get_subscriptions(&self) -> &[BatchResult<SubscriptionId>] { &self.subscriptions }
+}
+
+/// Watches the loaded aircraft and keeps the fields of the matching profile subscribed
+pub struct AircraftSwitcher {
+    profiles: Vec<AircraftProfile>,
+    identity: Option<AircraftIdentity>,
+    active: Option<usize>,
+    /// Identity seen by [`AircraftSwitcher::update`] that has not settled yet
+    candidate: Option<(AircraftIdentity, Instant)>,
+    aliases: AliasRegistry,
+    /// Byte elements of the identity datarefs, ICAO code first
+    datarefs: Vec<String>,
+    /// Consumers of the identity datarefs, released by [`AircraftSwitcher::stop`]
+    identity_subscriptions: Vec<SubscriptionId>,
+    /// Consumers of the fields of the active profile
+    profile_subscriptions: Vec<SubscriptionId>,
+}
+
+impl Default for AircraftSwitcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AircraftSwitcher {
+    pub fn new() -> Self {
+        let icao = (0..XP_AIRCRAFT_ICAO_LEN).map(|i| format!("{}[{}]", XP_AIRCRAFT_ICAO_DATAREF, i));
+        let path = (0..XP_AIRCRAFT_PATH_LEN).map(|i| format!("{}[{}]", XP_AIRCRAFT_PATH_DATAREF, i));
+        AircraftSwitcher {
+            profiles: Vec::new(),
+            identity: None,
+            active: None,
+            candidate: None,
+            aliases: AliasRegistry::new(),
+            datarefs: icao.chain(path).collect(),
+            identity_subscriptions: Vec::new(),
+            profile_subscriptions: Vec::new(),
+        }
+    }
+
+    /// Adds a profile, it is only matched against aircraft loaded after the next change
+    pub fn register(&mut self, profile: AircraftProfile) {
+        self.profiles.push(profile);
+    }
+
+    pub fn get_profiles(&self) -> &[AircraftProfile] { &self.profiles }
+    pub fn get_identity(&self) -> Option<&AircraftIdentity> { self.identity.as_ref() }
+
+    pub fn get_active_profile(&self) -> Option<&AircraftProfile> {
+        self.active.map(|i| &self.profiles[i])
+    }
+
+    /// Aliases resolved with the mappings of the active profile
+    pub fn get_aliases(&self) -> &AliasRegistry { &self.aliases }
+
+    /// For adding generic mappings and subscribing through aliases,
+    /// the aircraft mappings are replaced and the alias subscriptions moved on every change
+    pub fn get_aliases_mut(&mut self) -> &mut AliasRegistry { &mut self.aliases }
+
+    /// Subscribes the identity datarefs, fails with the first subscription that did
+    pub async fn start<B: Backend, T: Transport>(&mut self, session: &mut Session<B, T>) -> io::Result<()> {
+        let datarefs: Vec<(&str, i32, DataRefType)> = self.datarefs.iter()
+            .map(|e| (e.as_str(), XP_AIRCRAFT_FREQUENCY, DataRefType::Byte))
+            .collect();
+        let results = session.subscribe_many(&datarefs).await;
+        self.identity_subscriptions.extend(subscribed(&results));
+        results.into_iter().try_for_each(|e| e.into_result().map(|_| ()))
+    }
+
+    /// Releases the identity datarefs and the fields of the active profile,
+    /// datarefs other consumers subscribed to stay subscribed
+    pub async fn stop<B: Backend, T: Transport>(&mut self, session: &mut Session<B, T>) {
+        release_all(session, &mut self.profile_subscriptions).await;
+        release_all(session, &mut self.identity_subscriptions).await;
+        self.active = None;
+        self.identity = None;
+        self.candidate = None;
+        self.aliases.apply_profile(None);
+        if let Err(e) = self.aliases.resubscribe(session).await {
+            warn!("Failed to move alias subscriptions to the generic datarefs: {}", e);
+        }
+    }
+
+    /// Identity of the loaded aircraft, `None` until all of it has been received
+    pub fn read_identity<B: Backend, T: Transport>(&self, session: &Session<B, T>) -> Option<AircraftIdentity> {
+        let datarefs: Vec<&str> = self.datarefs.iter().map(|e| e.as_str()).collect();
+        let snapshot = session.snapshot(&datarefs);
+        let values: Vec<DataRefValueType> = datarefs.iter()
+            .map(|e| snapshot.get(e).copied().unwrap_or(DataRefValueType::Unknown))
+            .collect();
+        AircraftIdentity::from_values(&values)
+    }
+
+    /// Switches profiles once a changed aircraft identity has settled, meant to be called periodically
+    pub async fn update<B: Backend, T: Transport>(&mut self, session: &mut Session<B, T>) -> Option<AircraftChanged> {
+        let identity = self.read_identity(session)?;
+        if self.identity.as_ref() == Some(&identity) {
+            self.candidate = None;
+            return None;
+        }
+        match &self.candidate {
+            Some((candidate, since)) if *candidate == identity => {
+                if since.elapsed() < Duration::from_millis(XP_AIRCRAFT_SETTLE_MS) {
+                    return None;
+                }
+            }
+            _ => {
+                self.candidate = Some((identity, Instant::now()));
+                return None;
+            }
+        }
+        self.candidate = None;
+        Some(self.switch(identity, session).await)
+    }
+
+    /// Waits until a different aircraft is loaded and its identity settled, then switches profiles.
+    ///
+    /// Fails with `TimedOut` if no change shows up within `timeout`,
+    /// settling may take up to [`XP_AIRCRAFT_SETTLE_MS`] longer.
+    pub async fn wait_for_change<B: Backend, T: Transport>(&mut self, session: &mut Session<B, T>,
+                                                           timeout: Duration) -> io::Result<AircraftChanged> {
+        let datarefs: Vec<&str> = self.datarefs.iter().map(|e| e.as_str()).collect();
+        let deadline = Instant::now() + timeout;
+        let settle = Duration::from_millis(XP_AIRCRAFT_SETTLE_MS);
+
+        let identity = loop {
+            let current = self.identity.clone();
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let values = session.wait_for_all(&datarefs, |values| {
+                match AircraftIdentity::from_values(values) {
+                    Some(identity) => current.as_ref() != Some(&identity),
+                    None => false,
+                }
+            }, remaining).await.map_err(|e| match e.kind() {
+                io::ErrorKind::TimedOut => io::Error::new(io::ErrorKind::TimedOut,
+                                                          format!("No aircraft change within {:?}", timeout)),
+                _ => e,
+            })?;
+            let candidate = match AircraftIdentity::from_values(&values) {
+                Some(e) => e,
+                None => continue,
+            };
+
+            // Settled once nothing differs from the candidate for the whole settle time
+            let changed = session.wait_for_all(&datarefs, |values| {
+                AircraftIdentity::from_values(values).as_ref() != Some(&candidate)
+            }, settle).await;
+            match changed {
+                Ok(_) => continue,
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => break candidate,
+                Err(e) => return Err(e),
+            }
+        };
+
+        self.candidate = None;
+        Ok(self.switch(identity, session).await)
+    }
+
+    async fn switch<B: Backend, T: Transport>(&mut self, identity: AircraftIdentity,
+                                              session: &mut Session<B, T>) -> AircraftChanged {
+        let matching = self.profiles.iter()
+            .position(|profile| profile.get_aircraft().iter().any(|e| identity.matches(e)));
+        info!("Aircraft changed to {} ({}), profile {:?}", identity.icao, identity.path,
+            matching.map(|i| self.profiles[i].get_name()));
+
+        let mut subscriptions = Vec::new();
+        if matching != self.active {
+            release_all(session, &mut self.profile_subscriptions).await;
+            if let Some(profile) = matching.map(|i| &self.profiles[i]) {
+                subscriptions = session.subscribe_profile(profile).await;
+                self.profile_subscriptions.extend(subscribed(&subscriptions));
+            }
+            self.active = matching;
+            self.aliases.apply_profile(matching.map(|i| &self.profiles[i]));
+            if let Err(e) = self.aliases.resubscribe(session).await {
+                warn!("Failed to move alias subscriptions to the new aircraft: {}", e);
+            }
+        }
+
+        AircraftChanged {
+            previous: self.identity.replace(identity.clone()),
+            current: identity,
+            profile: matching.map(|i| self.profiles[i].get_name().to_string()),
+            subscriptions,
+        }
+    }
+}
+
+/// Ids of the subscriptions that succeeded
+fn subscribed(results: &[BatchResult<SubscriptionId>]) -> impl Iterator<Item = SubscriptionId> + '_ {
+    results.iter().filter_map(|e| e.get_result().as_ref().ok().copied())
+}
+
+/// Releases and forgets every consumer in `subscriptions`
+async fn release_all<B: Backend, T: Transport>(session: &mut Session<B, T>, subscriptions: &mut Vec<SubscriptionId>) {
+    for id in subscriptions.drain(..) {
+        if let Err(e) = session.release(id).await {
+            warn!("Failed to release subscription {:?}: {}", id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Byte values of `text` padded with NULs to `len`
+    fn bytes(text: &[u8], len: usize) -> Vec<DataRefValueType> {
+        let mut values: Vec<DataRefValueType> = text.iter().map(|&b| DataRefValueType::Byte(b)).collect();
+        values.resize(len, DataRefValueType::Byte(0));
+        values
+    }
+
+    fn identity_values(icao: &[u8], path: &[u8]) -> Vec<DataRefValueType> {
+        let mut values = bytes(icao, XP_AIRCRAFT_ICAO_LEN);
+        values.extend(bytes(path, XP_AIRCRAFT_PATH_LEN));
+        values
+    }
+
+    #[test]
+    fn decodes_until_the_first_nul() {
+        let values = bytes(b"B738\0XYZ", XP_AIRCRAFT_ICAO_LEN);
+        assert_eq!(decode_string(&values), Some("B738".to_string()));
+        assert_eq!(decode_string(&bytes(b"", XP_AIRCRAFT_ICAO_LEN)), Some(String::new()));
+    }
+
+    #[test]
+    fn decodes_strings_filling_every_byte() {
+        assert_eq!(decode_string(&bytes(b"ABCDEFGH", XP_AIRCRAFT_ICAO_LEN)), Some("ABCDEFGH".to_string()));
+
+        let path = [b'a'; XP_AIRCRAFT_PATH_LEN];
+        let decoded = decode_string(&bytes(&path, XP_AIRCRAFT_PATH_LEN)).unwrap();
+        assert_eq!(decoded.len(), XP_AIRCRAFT_PATH_LEN);
+    }
+
+    #[test]
+    fn decodes_bytes_above_ascii_as_latin1() {
+        // X-Plane strings are not guaranteed to be UTF-8, every byte becomes one char
+        let values = bytes(&[b'A', 0xE9, 0xFF], XP_AIRCRAFT_ICAO_LEN);
+        assert_eq!(decode_string(&values), Some("A\u{e9}\u{ff}".to_string()));
+    }
+
+    #[test]
+    fn missing_or_mistyped_bytes_decode_to_none() {
+        let mut values = bytes(b"B738", XP_AIRCRAFT_ICAO_LEN);
+        values[2] = DataRefValueType::Unknown;
+        assert_eq!(decode_string(&values), None);
+        values[2] = DataRefValueType::Float(51.0);
+        assert_eq!(decode_string(&values), None);
+    }
+
+    #[test]
+    fn identity_splits_icao_and_path() {
+        let identity = AircraftIdentity::from_values(
+            &identity_values(b"B738", b"Aircraft/B737-800X/b738_4k.acf")).unwrap();
+        assert_eq!(identity, AircraftIdentity::new("B738", "Aircraft/B737-800X/b738_4k.acf"));
+
+        let mut incomplete = identity_values(b"B738", b"Aircraft/B737-800X/b738_4k.acf");
+        incomplete[XP_AIRCRAFT_ICAO_LEN + 3] = DataRefValueType::Unknown;
+        assert_eq!(AircraftIdentity::from_values(&incomplete), None);
+        assert_eq!(AircraftIdentity::from_values(&incomplete[..XP_AIRCRAFT_ICAO_LEN]), Some(AircraftIdentity::new("B738", "")));
+    }
+
+    #[test]
+    fn matches_icao_exactly_and_path_partially_ignoring_case() {
+        let identity = AircraftIdentity::new("B738", "Aircraft/B737-800X/b738_4k.acf");
+        assert!(identity.matches("b738"));
+        assert!(identity.matches("B737-800X"));
+        assert!(identity.matches("b737-800x"));
+        assert!(!identity.matches("A320"));
+    }
+}
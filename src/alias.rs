@@ -0,0 +1,277 @@
+//! Logical names for values and commands that live under different datarefs or commands
+//! depending on the aircraft, e.g. `autopilot_course`.
+//!
+//! An [`AliasRegistry`] resolves an alias with the mappings of the current aircraft first,
+//! falling back to the generic `sim/` datarefs and commands. Aircraft mappings come from the
+//! `aliases` table of an aircraft profile, which
+//! [`AircraftSwitcher`](crate::aircraft::AircraftSwitcher) applies on every aircraft change:
+//!
+//! ```toml
+//! [aliases]
+//! autopilot_course = { dataref = "laminar/B738/autopilot/course_pilot", type = "int" }
+//! autopilot_engage = { command = "laminar/B738/autopilot/cmd_a_press" }
+//! ```
+//!
+//! Subscriptions made through [`AliasRegistry::subscribe`] follow the alias,
+//! [`AliasRegistry::resubscribe`] moves them to the datarefs the aliases resolve to now.
+
+use std::collections::HashMap;
+#[cfg(any(feature = "tokio", feature = "std"))]
+use std::io;
+
+#[cfg(any(feature = "tokio", feature = "std"))]
+use log::warn;
+
+#[cfg(any(feature = "tokio", feature = "std"))]
+use crate::backend::Backend;
+#[cfg(any(feature = "tokio", feature = "std"))]
+use crate::dataref::SubscriptionId;
+use crate::dataref_type::DataRefType;
+#[cfg(any(feature = "tokio", feature = "std"))]
+use crate::dataref_type::FromDataRefValue;
+#[cfg(any(feature = "tokio", feature = "std"))]
+use crate::error::XPlaneError;
+#[cfg(feature = "profiles")]
+use crate::profile::AircraftProfile;
+#[cfg(any(feature = "tokio", feature = "std"))]
+use crate::session::Session;
+#[cfg(any(feature = "tokio", feature = "std"))]
+use crate::transport::Transport;
+
+/// Dataref or command an alias stands for
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum AliasTarget {
+    DataRef {
+        dataref: String,
+        #[cfg_attr(feature = "serde", serde(rename = "type", default = "crate::dataref_type::default_type"))]
+        dataref_type: DataRefType,
+    },
+    Command {
+        command: String,
+    },
+}
+
+impl AliasTarget {
+    pub fn dataref(dataref: &str, dataref_type: DataRefType) -> Self {
+        AliasTarget::DataRef { dataref: dataref.to_string(), dataref_type }
+    }
+
+    pub fn command(command: &str) -> Self {
+        AliasTarget::Command { command: command.to_string() }
+    }
+}
+
+/// Generic mappings every aircraft falls back to
+const GENERIC_DATAREFS: [(&str, &str, DataRefType); 10] = [
+    ("autopilot_course", "sim/cockpit2/radios/actuators/hsi_obs_deg_mag_pilot", DataRefType::Float),
+    ("autopilot_heading", "sim/cockpit2/autopilot/heading_dial_deg_mag_pilot", DataRefType::Float),
+    ("autopilot_altitude", "sim/cockpit2/autopilot/altitude_dial_ft", DataRefType::Float),
+    ("autopilot_airspeed", "sim/cockpit2/autopilot/airspeed_dial_kts_mach", DataRefType::Float),
+    ("autopilot_vertical_speed", "sim/cockpit2/autopilot/vvi_dial_fpm", DataRefType::Float),
+    ("flap_handle", "sim/cockpit2/controls/flap_ratio", DataRefType::Float),
+    ("speedbrake_handle", "sim/cockpit2/controls/speedbrake_ratio", DataRefType::Float),
+    ("gear_handle", "sim/cockpit2/controls/gear_handle_down", DataRefType::Bool),
+    ("parking_brake", "sim/cockpit2/controls/parking_brake_ratio", DataRefType::Float),
+    ("transponder_code", "sim/cockpit2/radios/actuators/transponder_code", DataRefType::Int),
+];
+
+const GENERIC_COMMANDS: [(&str, &str); 6] = [
+    ("autopilot_engage", "sim/autopilot/servos_toggle"),
+    ("autopilot_disengage", "sim/autopilot/servos_off_any"),
+    ("flaps_up", "sim/flight_controls/flaps_up"),
+    ("flaps_down", "sim/flight_controls/flaps_down"),
+    ("gear_toggle", "sim/flight_controls/landing_gear_toggle"),
+    ("parking_brake_toggle", "sim/flight_controls/brakes_toggle_max"),
+];
+
+/// Subscription made through an alias
+#[cfg(any(feature = "tokio", feature = "std"))]
+#[derive(Debug, Clone)]
+struct AliasSubscription {
+    alias: String,
+    frequency: i32,
+    /// Dataref and consumer currently subscribed, `None` while the alias resolves to no dataref
+    current: Option<(String, SubscriptionId)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AliasRegistry {
+    generic: HashMap<String, AliasTarget>,
+    aircraft: HashMap<String, AliasTarget>,
+    /// Keyed by the id [`AliasRegistry::subscribe`] returned, which stays valid across resubscriptions
+    #[cfg(any(feature = "tokio", feature = "std"))]
+    subscriptions: HashMap<SubscriptionId, AliasSubscription>,
+}
+
+impl Default for AliasRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AliasRegistry {
+    /// Registry with the built-in generic `sim/` mappings
+    pub fn new() -> Self {
+        let datarefs = GENERIC_DATAREFS.iter()
+            .map(|(alias, dataref, dataref_type)| (alias.to_string(), AliasTarget::dataref(dataref, *dataref_type)));
+        let commands = GENERIC_COMMANDS.iter()
+            .map(|(alias, command)| (alias.to_string(), AliasTarget::command(command)));
+        let mut registry = Self::empty();
+        registry.generic = datarefs.chain(commands).collect();
+        registry
+    }
+
+    /// Registry without any generic mappings
+    pub fn empty() -> Self {
+        AliasRegistry {
+            generic: HashMap::new(),
+            aircraft: HashMap::new(),
+            #[cfg(any(feature = "tokio", feature = "std"))]
+            subscriptions: HashMap::new(),
+        }
+    }
+
+    /// Adds or replaces a generic mapping, see [`AliasRegistry::resubscribe`] for existing subscriptions
+    pub fn set_generic(&mut self, alias: &str, target: AliasTarget) {
+        self.generic.insert(alias.to_string(), target);
+    }
+
+    /// Replaces the mappings of the current aircraft, see [`AliasRegistry::resubscribe`] for existing subscriptions
+    pub fn set_aircraft<I: IntoIterator<Item = (String, AliasTarget)>>(&mut self, aliases: I) {
+        self.aircraft = aliases.into_iter().collect();
+    }
+
+    /// Takes the aircraft mappings from `profile`, `None` leaves only the generic ones
+    #[cfg(feature = "profiles")]
+    pub fn apply_profile(&mut self, profile: Option<&AircraftProfile>) {
+        match profile {
+            Some(profile) => self.set_aircraft(profile.get_aliases().clone()),
+            None => self.aircraft.clear(),
+        }
+    }
+
+    /// Target of `alias` for the current aircraft, falling back to the generic mapping
+    pub fn resolve(&self, alias: &str) -> Option<&AliasTarget> {
+        self.aircraft.get(alias).or_else(|| self.generic.get(alias))
+    }
+
+    /// Dataref and type of `alias`, `None` if it is unknown or a command
+    pub fn resolve_dataref(&self, alias: &str) -> Option<(&str, DataRefType)> {
+        match self.resolve(alias)? {
+            AliasTarget::DataRef { dataref, dataref_type } => Some((dataref, *dataref_type)),
+            AliasTarget::Command { .. } => None,
+        }
+    }
+
+    /// Command of `alias`, `None` if it is unknown or a dataref
+    pub fn resolve_command(&self, alias: &str) -> Option<&str> {
+        match self.resolve(alias)? {
+            AliasTarget::Command { command } => Some(command),
+            AliasTarget::DataRef { .. } => None,
+        }
+    }
+
+    /// All known aliases, aircraft and generic ones
+    pub fn aliases(&self) -> impl Iterator<Item = &str> {
+        let generic = self.generic.keys().filter(|e| !self.aircraft.contains_key(*e));
+        self.aircraft.keys().chain(generic).map(|e| e.as_str())
+    }
+}
+
+#[cfg(any(feature = "tokio", feature = "std"))]
+impl AliasRegistry {
+    fn dataref_or_error(&self, alias: &str) -> io::Result<(&str, DataRefType)> {
+        self.resolve_dataref(alias).ok_or_else(|| io::Error::new(
+            io::ErrorKind::NotFound, format!("No dataref known for alias {}", alias)))
+    }
+
+    /// Subscribes to the dataref `alias` currently resolves to.
+    ///
+    /// The returned id identifies the alias subscription, release it with [`AliasRegistry::release`].
+    pub async fn subscribe<B: Backend, T: Transport>(&mut self, session: &mut Session<B, T>, alias: &str,
+                                                     frequency: i32) -> io::Result<SubscriptionId> {
+        let (dataref, dataref_type) = self.dataref_or_error(alias)?;
+        let dataref = dataref.to_string();
+        let id = session.subscribe(&dataref, frequency, dataref_type).await?;
+        self.subscriptions.insert(id, AliasSubscription {
+            alias: alias.to_string(),
+            frequency,
+            current: Some((dataref, id)),
+        });
+        Ok(id)
+    }
+
+    /// Releases a subscription made with [`AliasRegistry::subscribe`]
+    pub async fn release<B: Backend, T: Transport>(&mut self, session: &mut Session<B, T>,
+                                                   id: SubscriptionId) -> io::Result<()> {
+        let subscription = self.subscriptions.remove(&id).ok_or_else(|| io::Error::new(
+            io::ErrorKind::NotFound, "Alias subscription not found"))?;
+        match subscription.current {
+            Some((_, current)) => session.release(current).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Moves the alias subscriptions whose alias now resolves to a different dataref,
+    /// meant to be called after the mappings changed.
+    ///
+    /// Subscriptions of aliases without a dataref are released until it resolves again.
+    /// Every subscription is tried, the first error is returned.
+    pub async fn resubscribe<B: Backend, T: Transport>(&mut self, session: &mut Session<B, T>) -> io::Result<()> {
+        let mut first_error = None;
+        let mut subscriptions = std::mem::take(&mut self.subscriptions);
+
+        for subscription in subscriptions.values_mut() {
+            let resolved = self.resolve_dataref(&subscription.alias);
+            let current = subscription.current.as_ref().map(|(dataref, _)| dataref.as_str());
+            if resolved.map(|(dataref, _)| dataref) == current {
+                continue;
+            }
+
+            if let Some((dataref, id)) = subscription.current.take() {
+                if let Err(e) = session.release(id).await {
+                    warn!("Failed to release dataref {} of alias {}: {}", dataref, subscription.alias, e);
+                    first_error.get_or_insert(e);
+                }
+            }
+            if let Some((dataref, dataref_type)) = resolved {
+                match session.subscribe(dataref, subscription.frequency, dataref_type).await {
+                    Ok(id) => subscription.current = Some((dataref.to_string(), id)),
+                    Err(e) => {
+                        warn!("Failed to subscribe dataref {} of alias {}: {}", dataref, subscription.alias, e);
+                        first_error.get_or_insert(e);
+                    }
+                }
+            }
+        }
+
+        self.subscriptions = subscriptions;
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Last value of the dataref `alias` resolves to, read as `V`
+    pub fn get<V: FromDataRefValue, B: Backend, T: Transport>(&self, session: &Session<B, T>,
+                                                               alias: &str) -> Result<V, XPlaneError> {
+        match self.resolve_dataref(alias) {
+            Some((dataref, _)) => session.get(dataref),
+            None => Err(XPlaneError::UnknownAlias(alias.to_string())),
+        }
+    }
+
+    /// Writes `value` to the dataref `alias` resolves to
+    pub async fn set<B: Backend, T: Transport>(&self, session: &Session<B, T>, alias: &str,
+                                               value: f32) -> io::Result<()> {
+        let (dataref, _) = self.dataref_or_error(alias)?;
+        session.set_dataref(dataref, value).await
+    }
+
+    /// Sends the command `alias` resolves to
+    pub async fn cmd<B: Backend, T: Transport>(&self, session: &Session<B, T>, alias: &str) -> io::Result<()> {
+        match self.resolve_command(alias) {
+            Some(command) => session.cmd(command).await,
+            None => Err(io::Error::new(io::ErrorKind::NotFound, format!("No command known for alias {}", alias))),
+        }
+    }
+}
@@ -1,31 +1,43 @@
 use std::io;
 use std::net::SocketAddrV4;
 
+use crate::backend::{Backend, DefaultBackend};
 use crate::beacon::Beacon;
 
-pub struct AutoDiscover {
-    pub beacon: Beacon,
+pub struct AutoDiscover<B: Backend = DefaultBackend> {
+    pub beacon: Beacon<B>,
 }
 
 impl AutoDiscover {
     pub async fn auto_discover_default(timeout: u64) -> Result<Self, io::Error> {
-        Ok(AutoDiscover {
-            beacon: Beacon::new(timeout).await?,
-        })
+        Self::auto_discover_default_with_backend(timeout).await
     }
 
     pub async fn auto_discover(beacon_addr: SocketAddrV4,
                                timeout: u64) -> Result<Self, io::Error> {
+        Self::auto_discover_with_backend(beacon_addr, timeout).await
+    }
+}
+
+impl<B: Backend> AutoDiscover<B> {
+    pub async fn auto_discover_default_with_backend(timeout: u64) -> Result<Self, io::Error> {
+        Ok(AutoDiscover {
+            beacon: Beacon::new_with_backend(timeout).await?,
+        })
+    }
+
+    pub async fn auto_discover_with_backend(beacon_addr: SocketAddrV4,
+                                            timeout: u64) -> Result<Self, io::Error> {
         Ok(AutoDiscover {
-            beacon: Beacon::new_with_address(beacon_addr, timeout).await?,
+            beacon: Beacon::new_with_address_with_backend(beacon_addr, timeout).await?,
         })
     }
 
-    pub fn get_beacon(&self) -> &Beacon {
+    pub fn get_beacon(&self) -> &Beacon<B> {
         &self.beacon
     }
 
-    pub fn get_beacon_mut(&mut self) -> &mut Beacon {
+    pub fn get_beacon_mut(&mut self) -> &mut Beacon<B> {
         &mut self.beacon
     }
 }
\ No newline at end of file
@@ -0,0 +1,105 @@
+//! Runtime backends driving the sockets and the receiving thread.
+//!
+//! The protocol code only talks to the [`Backend`] trait. The `tokio` feature (default) provides
+//! [`TokioBackend`], the `std` feature provides [`StdBackend`] built on `std::net::UdpSocket`
+//! and plain threads. Without either feature only the runtime-free core is available.
+
+use std::future::Future;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::transport::Transport;
+use crate::utils::Notifier;
+
+#[cfg(feature = "tokio")]
+mod tokio_backend;
+#[cfg(feature = "std")]
+mod std_backend;
+
+#[cfg(feature = "tokio")]
+pub use tokio_backend::TokioBackend;
+#[cfg(feature = "std")]
+pub use std_backend::{StdBackend, StdExecutor, StdUdpSocket};
+
+/// Backend used by `Session::manual` and the other constructors without an explicit backend
+#[cfg(feature = "tokio")]
+pub type DefaultBackend = TokioBackend;
+
+/// Backend used by `Session::manual` and the other constructors without an explicit backend
+#[cfg(all(feature = "std", not(feature = "tokio")))]
+pub type DefaultBackend = StdBackend;
+
+pub trait Backend: Send + Sync + Sized + 'static {
+    type Socket: Socket;
+    type Executor: Executor;
+
+    fn bind(address: SocketAddr) -> impl Future<Output = io::Result<Self::Socket>> + Send;
+
+    /// Calls `on_message` with every datagram received on `transport`, until `cancel` is cancelled
+    fn spawn_receiver<T, F>(transport: Arc<T>, cancel: CancellationToken, on_message: F)
+    where
+        T: Transport,
+        F: FnMut(&[u8]) + Send + 'static;
+
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send;
+
+    /// Executor for the blocking API
+    fn executor() -> io::Result<Self::Executor>;
+}
+
+/// UDP socket of a backend, also used for the multicast beacon
+pub trait Socket: Transport {
+    /// Receives a datagram, `Ok(None)` if nothing arrived within `timeout`
+    fn recv_from_timeout(&self, buf: &mut [u8], timeout: Duration)
+        -> impl Future<Output = io::Result<Option<(usize, SocketAddr)>>> + Send;
+
+    fn join_multicast_v4(&self, multiaddr: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()>;
+
+    fn leave_multicast_v4(&self, multiaddr: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()>;
+}
+
+/// Stops the receive loop of a backend, shared between the session and its receiving thread
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<CancellationState>,
+}
+
+#[derive(Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notifier: Notifier,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Release);
+        self.inner.notifier.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Completes once the token is cancelled
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notifier.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Runs futures to completion on the calling thread
+pub trait Executor: Send + Sync + 'static {
+    fn block_on<F: Future>(&self, future: F) -> F::Output;
+}
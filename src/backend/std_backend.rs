@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::thread::Thread;
+use std::time::{Duration, Instant};
+
+use log::{debug, error};
+
+use crate::backend::{Backend, CancellationToken, Executor, Socket};
+use crate::consts::XP_STD_RECEIVE_POLL_MS;
+use crate::transport::Transport;
+use crate::utils::{race, Either};
+
+/// Backend built on `std::net::UdpSocket` and a receiver thread, no async runtime involved.
+///
+/// Socket operations block the calling thread, the futures of this backend are meant
+/// to be driven by [`StdExecutor`], e.g. through `blocking::Session`.
+pub struct StdBackend;
+
+impl Backend for StdBackend {
+    type Socket = StdUdpSocket;
+    type Executor = StdExecutor;
+
+    async fn bind(address: SocketAddr) -> io::Result<StdUdpSocket> {
+        let inner = UdpSocket::bind(address)?;
+        // Blocking receives wake up regularly, so the receiver thread notices it was cancelled
+        inner.set_read_timeout(Some(Duration::from_millis(XP_STD_RECEIVE_POLL_MS)))?;
+        Ok(StdUdpSocket { inner })
+    }
+
+    fn spawn_receiver<T, F>(transport: Arc<T>, cancel: CancellationToken, mut on_message: F)
+    where
+        T: Transport,
+        F: FnMut(&[u8]) + Send + 'static,
+    {
+        let spawned = thread::Builder::new()
+            .name("xplane_udp receiver".to_string())
+            .spawn(move || {
+                let mut buffer = [0; 4096];
+
+                while !cancel.is_cancelled() {
+                    match StdExecutor.block_on(race(cancel.cancelled(), transport.recv_from(&mut buffer))) {
+                        Either::Left(()) => break,
+                        Either::Right(Ok((received, _))) => on_message(&buffer[..received]),
+                        Either::Right(Err(e)) if is_timeout(&e) => {}
+                        Either::Right(Err(e)) => {
+                            error!("Error receiving dataref: {}", e);
+                        }
+                    }
+                }
+                debug!("Receiver thread stopped");
+            });
+        if let Err(e) = spawned {
+            error!("Failed to spawn receiver thread: {}", e);
+        }
+    }
+
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+        Sleep {
+            deadline: Instant::now() + duration,
+            id: timer().next_id(),
+            waker: None,
+        }
+    }
+
+    fn executor() -> io::Result<StdExecutor> {
+        Ok(StdExecutor)
+    }
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
+
+pub struct StdUdpSocket {
+    inner: UdpSocket,
+}
+
+impl StdUdpSocket {
+    pub fn get_ref(&self) -> &UdpSocket {
+        &self.inner
+    }
+}
+
+impl Transport for StdUdpSocket {
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        self.inner.send_to(buf, target)
+    }
+
+    fn try_send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        self.inner.send_to(buf, target)
+    }
+
+    /// Blocks for at most the read timeout of the socket, which surfaces as a `WouldBlock` error
+    async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.inner.recv_from(buf)
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    async fn connect(&self, address: SocketAddr) -> io::Result<()> {
+        self.inner.connect(address)
+    }
+}
+
+impl Socket for StdUdpSocket {
+
+    async fn recv_from_timeout(&self, buf: &mut [u8], timeout: Duration) -> io::Result<Option<(usize, SocketAddr)>> {
+        // A zero read timeout means blocking forever
+        self.inner.set_read_timeout(Some(timeout.max(Duration::from_millis(1))))?;
+        let received = self.inner.recv_from(buf);
+        self.inner.set_read_timeout(Some(Duration::from_millis(XP_STD_RECEIVE_POLL_MS)))?;
+        match received {
+            Ok(received) => Ok(Some(received)),
+            Err(e) if is_timeout(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn join_multicast_v4(&self, multiaddr: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()> {
+        self.inner.join_multicast_v4(&multiaddr, &interface)
+    }
+
+    fn leave_multicast_v4(&self, multiaddr: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()> {
+        self.inner.leave_multicast_v4(&multiaddr, &interface)
+    }
+}
+
+/// Timer future, woken by the shared [`Timer`] thread
+struct Sleep {
+    deadline: Instant,
+    id: u64,
+    /// Waker registered with the timer, `None` until the first pending poll
+    waker: Option<Waker>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        // Polled again by the same task, the registered waker is still good
+        if self.waker.as_ref().is_some_and(|e| e.will_wake(cx.waker())) {
+            return Poll::Pending;
+        }
+        timer().register((self.deadline, self.id), cx.waker().clone());
+        self.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if self.waker.is_some() {
+            timer().cancel((self.deadline, self.id));
+        }
+    }
+}
+
+/// Deadlines of all pending [`Sleep`]s, served by a single thread
+struct Timer {
+    /// Keyed by deadline first, so the next one to expire comes first
+    entries: Mutex<BTreeMap<(Instant, u64), Waker>>,
+    changed: Condvar,
+    next_id: AtomicU64,
+}
+
+fn timer() -> &'static Timer {
+    static TIMER: OnceLock<Timer> = OnceLock::new();
+    TIMER.get_or_init(|| {
+        thread::Builder::new()
+            .name("xplane_udp timer".to_string())
+            .spawn(|| timer().run())
+            .expect("Failed to spawn timer thread");
+        Timer {
+            entries: Mutex::new(BTreeMap::new()),
+            changed: Condvar::new(),
+            next_id: AtomicU64::new(0),
+        }
+    })
+}
+
+impl Timer {
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn register(&self, key: (Instant, u64), waker: Waker) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let earliest = entries.keys().next().is_none_or(|e| key < *e);
+        entries.insert(key, waker);
+        // Only a new earliest deadline shortens the wait of the timer thread
+        if earliest {
+            self.changed.notify_one();
+        }
+    }
+
+    fn cancel(&self, key: (Instant, u64)) {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+    }
+
+    fn run(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            let now = Instant::now();
+            let mut expired = Vec::new();
+            while let Some(entry) = entries.first_entry() {
+                if entry.key().0 > now {
+                    break;
+                }
+                expired.push(entry.remove());
+            }
+
+            if !expired.is_empty() {
+                // Woken without the lock, so a waker polling right away can register again
+                drop(entries);
+                expired.into_iter().for_each(Waker::wake);
+                entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+                continue;
+            }
+
+            entries = match entries.keys().next() {
+                Some(&(deadline, _)) => self.changed.wait_timeout(entries, deadline - now)
+                    .unwrap_or_else(|e| e.into_inner()).0,
+                None => self.changed.wait(entries).unwrap_or_else(|e| e.into_inner()),
+            };
+        }
+    }
+}
+
+/// Executor parking the calling thread until its future is woken
+pub struct StdExecutor;
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+impl Executor for StdExecutor {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                // An unpark issued before parking makes park return immediately, no wake-up is lost
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sleep(duration: Duration) -> Sleep {
+        Sleep { deadline: Instant::now() + duration, id: timer().next_id(), waker: None }
+    }
+
+    #[test]
+    fn earlier_deadline_completes_first() {
+        let start = Instant::now();
+        let finished = StdExecutor.block_on(race(sleep(Duration::from_secs(5)), sleep(Duration::from_millis(20))));
+
+        assert!(matches!(finished, Either::Right(())));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn dropped_sleep_is_removed_from_the_timer() {
+        let pending = sleep(Duration::from_secs(60));
+        let key = (pending.deadline, pending.id);
+        StdExecutor.block_on(race(pending, sleep(Duration::from_millis(10))));
+
+        assert!(!timer().entries.lock().unwrap().contains_key(&key));
+    }
+}
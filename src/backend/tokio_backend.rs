@@ -0,0 +1,106 @@
+use std::future::Future;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, error};
+use tokio::net::UdpSocket;
+use tokio::runtime::{Builder, Runtime};
+use tokio::task;
+
+use crate::backend::{Backend, CancellationToken, Executor, Socket};
+use crate::transport::Transport;
+use crate::utils::{race, Either};
+
+/// Backend running on the tokio runtime of the caller
+pub struct TokioBackend;
+
+impl Backend for TokioBackend {
+    type Socket = UdpSocket;
+    type Executor = Runtime;
+
+    async fn bind(address: SocketAddr) -> io::Result<UdpSocket> {
+        UdpSocket::bind(address).await
+    }
+
+    fn spawn_receiver<T, F>(transport: Arc<T>, cancel: CancellationToken, mut on_message: F)
+    where
+        T: Transport,
+        F: FnMut(&[u8]) + Send + 'static,
+    {
+        task::spawn(async move {
+            let mut buffer = [0; 4096];
+
+            loop {
+                match race(cancel.cancelled(), transport.recv_from(&mut buffer)).await {
+                    Either::Left(()) => break,
+                    Either::Right(Ok((received, _))) => on_message(&buffer[..received]),
+                    Either::Right(Err(e)) => {
+                        error!("Error receiving dataref: {}", e);
+                    }
+                }
+            }
+            debug!("Receiving task stopped");
+        });
+    }
+
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+        tokio::time::sleep(duration)
+    }
+
+    fn executor() -> io::Result<Runtime> {
+        // A single worker is enough to drive the receiving thread while the caller is blocked elsewhere
+        Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("xplane_udp")
+            .enable_all()
+            .build()
+    }
+}
+
+impl Transport for UdpSocket {
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        UdpSocket::send_to(self, buf, target).await
+    }
+
+    fn try_send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        UdpSocket::try_send_to(self, buf, target)
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        UdpSocket::recv_from(self, buf).await
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        UdpSocket::local_addr(self)
+    }
+
+    async fn connect(&self, address: SocketAddr) -> io::Result<()> {
+        UdpSocket::connect(self, address).await
+    }
+}
+
+impl Socket for UdpSocket {
+    async fn recv_from_timeout(&self, buf: &mut [u8], timeout: Duration) -> io::Result<Option<(usize, SocketAddr)>> {
+        match tokio::time::timeout(timeout, self.recv_from(buf)).await {
+            Ok(Ok(received)) => Ok(Some(received)),
+            Ok(Err(e)) => Err(e),
+            Err(_elapsed) => Ok(None),
+        }
+    }
+
+    fn join_multicast_v4(&self, multiaddr: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()> {
+        UdpSocket::join_multicast_v4(self, multiaddr, interface)
+    }
+
+    fn leave_multicast_v4(&self, multiaddr: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()> {
+        UdpSocket::leave_multicast_v4(self, multiaddr, interface)
+    }
+}
+
+impl Executor for Runtime {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        Runtime::block_on(self, future)
+    }
+}
@@ -3,38 +3,38 @@ use std::io;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::time::Duration;
 
-use tokio::net::UdpSocket;
-use tokio::time::timeout;
-
+use crate::backend::{Backend, DefaultBackend, Socket};
 use crate::beacon_data::BeaconData;
 use crate::consts::{
-    STANDARD_BUFFER_SIZE, XP_MULTICAST_ADDR, XP_MULTICAST_GRP,
+    STANDARD_BUFFER_SIZE, XP_MULTICAST_ADDR,
     XP_MULTICAST_PARSE_MAX_TRIES, XP_MULTICAST_TIMEOUT_MAX_TRIES,
 };
 
-pub struct Beacon {
+pub struct Beacon<B: Backend = DefaultBackend> {
     data: Option<BeaconData>,
     xp_multicast_address: SocketAddrV4,
-    xp_multicast_beacon_socket: UdpSocket,
+    xp_multicast_beacon_socket: B::Socket,
+    /// Whether the socket is currently a member of the multicast group
+    joined: bool,
     timeout_duration: Duration,
 }
 
 impl Beacon {
     pub async fn new(timeout: u64) -> io::Result<Self> {
-        let socket = Self::init_beacon(XP_MULTICAST_ADDR).await?;
-        let xp_multicast_address = XP_MULTICAST_ADDR;
+        Self::new_with_backend(timeout).await
+    }
 
-        let per_attempt_timeout = Duration::from_millis(timeout / (XP_MULTICAST_TIMEOUT_MAX_TRIES as u64 + 1));
+    pub async fn new_with_address(beacon_address: SocketAddrV4, timeout: u64) -> io::Result<Self> {
+        Self::new_with_address_with_backend(beacon_address, timeout).await
+    }
+}
 
-        Ok(Beacon {
-            data: None,
-            xp_multicast_address,
-            xp_multicast_beacon_socket: socket,
-            timeout_duration: per_attempt_timeout,
-        })
+impl<B: Backend> Beacon<B> {
+    pub async fn new_with_backend(timeout: u64) -> io::Result<Self> {
+        Self::new_with_address_with_backend(XP_MULTICAST_ADDR, timeout).await
     }
 
-    pub async fn new_with_address(beacon_address: SocketAddrV4, timeout: u64) -> io::Result<Self> {
+    pub async fn new_with_address_with_backend(beacon_address: SocketAddrV4, timeout: u64) -> io::Result<Self> {
         let socket = Self::init_beacon(beacon_address).await?;
         let per_attempt_timeout = Duration::from_millis(timeout / (XP_MULTICAST_TIMEOUT_MAX_TRIES as u64 + 1));
 
@@ -42,22 +42,26 @@ impl Beacon {
             data: None,
             xp_multicast_address: beacon_address,
             xp_multicast_beacon_socket: socket,
+            joined: false,
             timeout_duration: per_attempt_timeout,
         })
     }
 
-    async fn init_beacon(beacon_address: SocketAddrV4) -> io::Result<UdpSocket> {
+    async fn init_beacon(beacon_address: SocketAddrV4) -> io::Result<B::Socket> {
         if !beacon_address.ip().is_multicast() {
             error!("Invalid multicast address: {}", beacon_address.ip());
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid multicast address"));
         }
 
-        let beacon_socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, beacon_address.port())).await?;
+        let beacon_socket = B::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, beacon_address.port()).into()).await?;
 
         Ok(beacon_socket)
     }
 
     async fn connect_beacon(&mut self) -> io::Result<()> {
+        if self.joined {
+            return Ok(());
+        }
         info!("Connecting to X-Plane multicast group: {}", self.xp_multicast_address);
 
         self.xp_multicast_beacon_socket.join_multicast_v4(
@@ -66,15 +70,26 @@ impl Beacon {
             // Listen on all interfaces
             Ipv4Addr::UNSPECIFIED,
         )?;
+        self.joined = true;
 
         Ok(())
     }
 
-    pub async fn close_beacon(&self) -> io::Result<()> {
+    /// Leaves the multicast group, does nothing if the group was not joined
+    pub async fn close_beacon(&mut self) -> io::Result<()> {
+        self.leave_group()
+    }
+
+    fn leave_group(&mut self) -> io::Result<()> {
+        if !self.joined {
+            return Ok(());
+        }
+
         self.xp_multicast_beacon_socket.leave_multicast_v4(
-            XP_MULTICAST_GRP,
+            *self.xp_multicast_address.ip(),
             Ipv4Addr::UNSPECIFIED,
         )?;
+        self.joined = false;
 
         debug!("Left X-Plane multicast group: {}", self.xp_multicast_address);
         Ok(())
@@ -90,14 +105,12 @@ impl Beacon {
 
         loop {
             // Try receiving within a timeout
-            let recv_result = timeout
-                (
-                    self.timeout_duration,
-                    self.xp_multicast_beacon_socket.recv_from(&mut buf),
-                ).await;
+            let recv_result = self.xp_multicast_beacon_socket
+                .recv_from_timeout(&mut buf, self.timeout_duration)
+                .await;
 
             match recv_result {
-                Ok(Ok((size, src_addr))) => {
+                Ok(Some((size, src_addr))) => {
                     let message = {
                         let mut msg_buf = [0u8; STANDARD_BUFFER_SIZE];
                         msg_buf[..size].copy_from_slice(&buf[..size]);
@@ -146,13 +159,13 @@ impl Beacon {
                         }
                     }
                 }
-                Ok(Err(e)) => {
+                Err(e) => {
                     // Non-timeout related error receiving data
                     error!("Error receiving beacon messages: {}", e);
                     return Err(e);
                 }
-                Err(_elapsed) => {
-                    // The timeout future elapsed, meaning recv_from did not complete in time
+                Ok(None) => {
+                    // The timeout elapsed, meaning recv_from did not complete in time
                     if timeout_tries <= XP_MULTICAST_TIMEOUT_MAX_TRIES {
                         debug!(
                             "Timeout receiving beacon message, retrying {}/{}",
@@ -187,4 +200,12 @@ impl Beacon {
     pub fn get_address(&self) -> SocketAddrV4 {
         self.xp_multicast_address
     }
-}
\ No newline at end of file
+}
+
+impl<B: Backend> Drop for Beacon<B> {
+    fn drop(&mut self) {
+        if let Err(e) = self.leave_group() {
+            error!("Failed to leave X-Plane multicast group: {}", e);
+        }
+    }
+}
@@ -3,7 +3,18 @@ use std::net::SocketAddr;
 
 use crate::consts::BEACON_PREFIX;
 
-#[derive(Debug)]
+/// Contents of a BECN message.
+///
+/// With the `serde` feature, it serializes as an object with the field names below,
+/// the source address as a string:
+///
+/// ```json
+/// {"source": "192.168.1.10:49707", "beacon_major_version": 1, "beacon_minor_version": 2,
+///  "application_host_id": 1, "version_number": 121400, "role": 1, "port": 49000,
+///  "computer_name": "sim-pc"}
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BeaconData {
     /// Source address of the beacon message
     source: SocketAddr,
@@ -18,6 +29,7 @@ pub struct BeaconData {
 }
 
 impl BeaconData {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(beacon_major_version: u8,
                beacon_minor_version: u8,
                application_host_id: i32,
@@ -80,4 +92,28 @@ impl BeaconData {
     pub fn get_port(&self) -> u16 { self.port }
     pub fn get_computer_name(&self) -> &str { &self.computer_name }
     pub fn get_source(&self) -> &SocketAddr { &self.source }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    const JSON: &str = concat!(
+        r#"{"source":"192.168.1.10:49707","beacon_major_version":1,"beacon_minor_version":2,"#,
+        r#""application_host_id":1,"version_number":121400,"role":1,"port":49000,"computer_name":"sim-pc"}"#,
+    );
+
+    #[test]
+    fn round_trips_in_documented_format() {
+        let beacon = BeaconData::new(1, 2, 1, 121400, 1, 49000, "sim-pc".to_string(),
+                                     "192.168.1.10:49707".parse().unwrap());
+        assert_eq!(serde_json::to_string(&beacon).unwrap(), JSON);
+
+        let beacon: BeaconData = serde_json::from_str(JSON).unwrap();
+        assert_eq!(beacon.get_source(), &"192.168.1.10:49707".parse::<SocketAddr>().unwrap());
+        assert_eq!(beacon.get_version_number(), 121400);
+        assert_eq!(beacon.get_port(), 49000);
+        assert_eq!(beacon.get_computer_name(), "sim-pc");
+        assert_eq!(serde_json::to_string(&beacon).unwrap(), JSON);
+    }
+}
@@ -0,0 +1,270 @@
+//! Synchronous wrapper around [`crate::session::Session`].
+//!
+//! The blocking session owns the executor of its backend, for tokio a private runtime which keeps
+//! the receiving thread running between calls. Every call blocks the current thread until the
+//! underlying operation completes, so it must not be used from within an async context.
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::error;
+
+use crate::auto_discover::AutoDiscover;
+use crate::backend::{Backend, DefaultBackend, Executor};
+use crate::beacon::Beacon;
+use crate::catalog::Catalog;
+use crate::command_catalog::CommandCatalog;
+use crate::command_handler::AlertMessage;
+use crate::dataref::{DataRefMeta, SubscriptionId, SubscriptionStatus};
+use crate::dataref_handle::{DataRefHandle, DataRefScalar};
+use crate::dataref_handler::BatchResult;
+use crate::dataref_type::{DataRefType, DataRefValueType, FromDataRefValue};
+use crate::error::XPlaneError;
+use crate::group::DataRefGroup;
+#[cfg(feature = "profiles")]
+use crate::profile::AircraftProfile;
+use crate::session;
+use crate::snapshot::Snapshot;
+use crate::transport::Transport;
+use crate::units::{Unit, UnitOf};
+
+pub struct Session<B: Backend = DefaultBackend, T: Transport = <B as Backend>::Socket> {
+    // Dropped before the runtime it was created on
+    inner: session::Session<B, T>,
+    runtime: B::Executor,
+}
+
+impl Session {
+    pub fn manual(xp_receiving_address: SocketAddr,
+                  xp_sending_address: SocketAddr) -> io::Result<Self> {
+        Self::manual_with_backend(xp_receiving_address, xp_sending_address)
+    }
+
+    /// Discovers X-Plane on the default multicast group and intercepts its beacon
+    pub fn auto_discover(timeout: u64) -> io::Result<Self> {
+        Self::auto_discover_with_backend(timeout)
+    }
+}
+
+impl<B: Backend> Session<B> {
+    pub fn manual_with_backend(xp_receiving_address: SocketAddr,
+                               xp_sending_address: SocketAddr) -> io::Result<Self> {
+        let runtime = B::executor()?;
+        let inner = runtime.block_on(session::Session::manual_with_backend(xp_receiving_address, xp_sending_address))?;
+        Ok(Session { inner, runtime })
+    }
+
+    pub fn auto_discover_with_backend(timeout: u64) -> io::Result<Self> {
+        let runtime = B::executor()?;
+        let inner = runtime.block_on(async {
+            let auto_discover = AutoDiscover::auto_discover_default_with_backend(timeout).await?;
+            session::Session::intercept_beacon(auto_discover).await.map_err(|(e, _)| {
+                error!("Failed to intercept X-Plane: {}", e);
+                e
+            })
+        })?;
+        Ok(Session { inner, runtime })
+    }
+}
+
+impl<T: Transport> Session<DefaultBackend, T> {
+    pub fn with_transport(transport: T, xp_receiving_address: SocketAddr,
+                          xp_sending_address: SocketAddr) -> io::Result<Self> {
+        Self::with_transport_with_backend(transport, xp_receiving_address, xp_sending_address)
+    }
+}
+
+impl<B: Backend, T: Transport> Session<B, T> {
+    pub fn with_transport_with_backend(transport: T, xp_receiving_address: SocketAddr,
+                                       xp_sending_address: SocketAddr) -> io::Result<Self> {
+        let runtime = B::executor()?;
+        let inner = session::Session::with_transport_with_backend(transport, xp_receiving_address, xp_sending_address);
+        Ok(Session { inner, runtime })
+    }
+
+    pub fn get_beacon(&self) -> &Option<Beacon<B>> {
+        self.inner.get_beacon()
+    }
+
+    pub fn run(&mut self) -> io::Result<()> {
+        self.runtime.block_on(self.inner.run())
+    }
+
+    pub fn subscribe(&mut self, dataref: &str, frequency: i32, dataref_type: DataRefType) -> io::Result<SubscriptionId> {
+        self.runtime.block_on(self.inner.subscribe(dataref, frequency, dataref_type))
+    }
+
+    pub fn subscribe_inferred(&mut self, dataref: &str, frequency: i32) -> io::Result<SubscriptionId> {
+        self.runtime.block_on(self.inner.subscribe_inferred(dataref, frequency))
+    }
+
+    pub fn subscribe_confirmed(&mut self, dataref: &str, frequency: i32, dataref_type: DataRefType) -> io::Result<SubscriptionId> {
+        self.runtime.block_on(self.inner.subscribe_confirmed(dataref, frequency, dataref_type))
+    }
+
+    pub fn subscribe_handle<V: DataRefScalar>(&mut self, dataref: &str, frequency: i32) -> io::Result<DataRefHandle<V>> {
+        self.runtime.block_on(self.inner.subscribe_handle(dataref, frequency))
+    }
+
+    pub fn subscribe_many(&mut self, datarefs: &[(&str, i32, DataRefType)]) -> Vec<BatchResult<SubscriptionId>> {
+        self.runtime.block_on(self.inner.subscribe_many(datarefs))
+    }
+
+    pub fn release(&mut self, id: SubscriptionId) -> io::Result<()> {
+        self.runtime.block_on(self.inner.release(id))
+    }
+
+    pub fn release_many(&mut self, ids: &[SubscriptionId]) -> Vec<BatchResult<()>> {
+        self.runtime.block_on(self.inner.release_many(ids))
+    }
+
+    pub fn unsubscribe(&mut self, dataref: &str) -> io::Result<()> {
+        self.runtime.block_on(self.inner.unsubscribe(dataref))
+    }
+
+    pub fn unsubscribe_many(&mut self, datarefs: &[&str]) -> Vec<BatchResult<()>> {
+        self.runtime.block_on(self.inner.unsubscribe_many(datarefs))
+    }
+
+    pub fn unsubscribe_all(&mut self) -> io::Result<()> {
+        self.runtime.block_on(self.inner.unsubscribe_all())
+    }
+
+    pub fn subscription_status(&self, dataref: &str) -> Option<SubscriptionStatus> {
+        self.inner.subscription_status(dataref)
+    }
+
+    pub fn set_confirm_timeout(&mut self, confirm_timeout: Duration) {
+        self.inner.set_confirm_timeout(confirm_timeout);
+    }
+
+    pub fn wait_for<F>(&self, dataref: &str, predicate: F, timeout: Duration) -> io::Result<DataRefValueType>
+    where
+        F: Fn(&DataRefValueType) -> bool,
+    {
+        self.runtime.block_on(self.inner.wait_for(dataref, predicate, timeout))
+    }
+
+    pub fn wait_for_all<F>(&self, datarefs: &[&str], predicate: F, timeout: Duration) -> io::Result<Vec<DataRefValueType>>
+    where
+        F: Fn(&[DataRefValueType]) -> bool,
+    {
+        self.runtime.block_on(self.inner.wait_for_all(datarefs, predicate, timeout))
+    }
+
+    pub fn cmd(&self, command: &str) -> io::Result<()> {
+        self.runtime.block_on(self.inner.cmd(command))
+    }
+
+    pub fn set_dataref(&self, dataref: &str, value: f32) -> io::Result<()> {
+        self.runtime.block_on(self.inner.set_dataref(dataref, value))
+    }
+
+    pub fn alert(&self, message: AlertMessage) -> io::Result<()> {
+        self.runtime.block_on(self.inner.alert(message))
+    }
+
+    pub fn get_dataref(&self, dataref: &str) -> Option<DataRefValueType> {
+        self.inner.get_dataref(dataref)
+    }
+
+    pub fn get<V: FromDataRefValue>(&self, dataref: &str) -> Result<V, XPlaneError> {
+        self.inner.get(dataref)
+    }
+
+    pub fn subscribe_with_unit(&mut self, dataref: &str, frequency: i32, dataref_type: DataRefType,
+                               unit: Unit) -> io::Result<SubscriptionId> {
+        self.runtime.block_on(self.inner.subscribe_with_unit(dataref, frequency, dataref_type, unit))
+    }
+
+    pub fn set_catalog(&mut self, catalog: Option<Arc<Catalog>>) {
+        self.inner.set_catalog(catalog);
+    }
+
+    pub fn get_catalog(&self) -> Option<&Catalog> {
+        self.inner.get_catalog()
+    }
+
+    pub fn set_command_catalog(&mut self, catalog: Option<Arc<CommandCatalog>>) {
+        self.inner.set_command_catalog(catalog);
+    }
+
+    pub fn get_command_catalog(&self) -> Option<&CommandCatalog> {
+        self.inner.get_command_catalog()
+    }
+
+    pub fn set_unit(&self, dataref: &str, unit: Option<Unit>) -> io::Result<()> {
+        self.inner.set_unit(dataref, unit)
+    }
+
+    pub fn get_unit(&self, dataref: &str) -> Option<Unit> {
+        self.inner.get_unit(dataref)
+    }
+
+    pub fn get_as<U: UnitOf>(&self, dataref: &str) -> Result<f64, XPlaneError> {
+        self.inner.get_as::<U>(dataref)
+    }
+
+    pub fn get_dataref_with_meta(&self, dataref: &str) -> Option<(DataRefValueType, DataRefMeta)> {
+        self.inner.get_dataref_with_meta(dataref)
+    }
+
+    pub fn is_stale(&self, dataref: &str, max_age: Duration) -> bool {
+        self.inner.is_stale(dataref, max_age)
+    }
+
+    pub fn snapshot(&self, datarefs: &[&str]) -> Snapshot {
+        self.inner.snapshot(datarefs)
+    }
+
+    pub fn subscribe_group<G: DataRefGroup>(&mut self) -> Vec<BatchResult<SubscriptionId>> {
+        self.runtime.block_on(self.inner.subscribe_group::<G>())
+    }
+
+    pub fn read_group<G: DataRefGroup>(&self) -> Result<G, XPlaneError> {
+        self.inner.read_group()
+    }
+
+    #[cfg(feature = "profiles")]
+    pub fn subscribe_profile(&mut self, profile: &AircraftProfile) -> Vec<BatchResult<SubscriptionId>> {
+        self.runtime.block_on(self.inner.subscribe_profile(profile))
+    }
+
+    #[cfg(feature = "profiles")]
+    pub fn unsubscribe_profile(&mut self, subscriptions: &[SubscriptionId]) -> Vec<BatchResult<()>> {
+        self.runtime.block_on(self.inner.unsubscribe_profile(subscriptions))
+    }
+
+    #[cfg(feature = "profiles")]
+    pub fn run_action(&self, profile: &AircraftProfile, name: &str) -> io::Result<()> {
+        self.runtime.block_on(self.inner.run_action(profile, name))
+    }
+
+    pub fn snapshot_all(&self) -> Snapshot {
+        self.inner.snapshot_all()
+    }
+
+    pub fn set_history_depth(&self, id: SubscriptionId, depth: usize) -> io::Result<()> {
+        self.inner.set_history_depth(id, depth)
+    }
+
+    pub fn subscribe_with_history(&mut self, dataref: &str, frequency: i32, dataref_type: DataRefType,
+                                  depth: usize) -> io::Result<SubscriptionId> {
+        self.runtime.block_on(self.inner.subscribe_with_history(dataref, frequency, dataref_type, depth))
+    }
+
+    pub fn history(&self, dataref: &str) -> Option<Vec<(Instant, DataRefValueType)>> {
+        self.inner.history(dataref)
+    }
+
+    pub fn set_shutdown_timeout(&mut self, shutdown_timeout: Duration) {
+        self.inner.set_shutdown_timeout(shutdown_timeout);
+    }
+
+    pub fn shutdown(self) {
+        let Session { inner, runtime } = self;
+        runtime.block_on(inner.shutdown());
+    }
+}
@@ -0,0 +1,263 @@
+//! Dataref catalog parsed from X-Plane's `Resources/plugins/DataRefs.txt`.
+//!
+//! Every line of the file describes one dataref, separated by tabs:
+//! name, type (`int`, `float`, `double`, `byte`, arrays as `float[8]`), writability (`y`/`n`),
+//! units and a description, the last two being optional.
+//!
+//! Set on a [`Session`](crate::session::Session), the catalog rejects subscriptions of unknown
+//! datarefs, infers their [`DataRefType`] and warns about writes to read-only datarefs.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use crate::catalog_file::{self, CatalogLine};
+use crate::dataref_type::DataRefType;
+use crate::units::Unit;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogEntry {
+    name: String,
+    value_type: String,
+    array_len: Option<usize>,
+    writable: bool,
+    units: Option<String>,
+    description: Option<String>,
+}
+
+impl CatalogEntry {
+    pub fn get_name(&self) -> &str { &self.name }
+    /// Element type as listed, e.g. `float` for `float[8]`
+    pub fn get_value_type(&self) -> &str { &self.value_type }
+    /// Number of elements of an array dataref, `None` for scalars
+    pub fn get_array_len(&self) -> Option<usize> { self.array_len }
+    pub fn is_writable(&self) -> bool { self.writable }
+    pub fn get_units(&self) -> Option<&str> { self.units.as_deref() }
+    pub fn get_description(&self) -> Option<&str> { self.description.as_deref() }
+
+    /// Type to subscribe the dataref, or one of its elements, with
+    pub fn infer_type(&self) -> Option<DataRefType> {
+        // Some datarefs list alternatives such as `int|float`, the first one is what X-Plane publishes
+        let dataref_type = match self.value_type.split('|').next()? {
+            "int" if self.units.as_deref() == Some("boolean") => DataRefType::Bool,
+            "int" => DataRefType::Int,
+            "float" => DataRefType::Float,
+            "double" => DataRefType::Double,
+            "byte" => DataRefType::Byte,
+            _ => return None,
+        };
+        Some(dataref_type)
+    }
+
+    /// Listed units as a [`Unit`], if they are one the library converts
+    pub fn get_unit(&self) -> Option<Unit> {
+        self.units.as_deref()?.parse().ok()
+    }
+}
+
+impl CatalogLine for CatalogEntry {
+    fn parse(line: &str) -> Option<CatalogEntry> {
+        let mut fields = line.split('\t').map(str::trim);
+        let name = fields.next().filter(|e| e.contains('/'))?;
+        let (value_type, array_len) = parse_type(fields.next()?)?;
+        let writable = match fields.next()? {
+            "y" => true,
+            "n" => false,
+            _ => return None,
+        };
+        let units = fields.next()
+            .map(|e| e.trim_start_matches('[').trim_end_matches(']'))
+            .filter(|e| !e.is_empty() && *e != "???")
+            .map(str::to_string);
+        let description = fields.next().filter(|e| !e.is_empty()).map(str::to_string);
+
+        Some(CatalogEntry {
+            name: name.to_string(),
+            value_type,
+            array_len,
+            writable,
+            units,
+            description,
+        })
+    }
+
+    fn name(&self) -> &str { &self.name }
+}
+
+/// Splits `float[4][2]` into the element type and the total number of elements
+fn parse_type(value_type: &str) -> Option<(String, Option<usize>)> {
+    let (base, dimensions) = match value_type.find('[') {
+        Some(i) => value_type.split_at(i),
+        None => return Some((value_type.to_string(), None)),
+    };
+    let mut len = 1usize;
+    for dimension in dimensions.split_terminator(']') {
+        len = len.checked_mul(dimension.strip_prefix('[')?.parse().ok()?)?;
+    }
+    Some((base.to_string(), Some(len)))
+}
+
+/// Splits `name[3]` into the dataref name and the element index
+fn split_index(dataref: &str) -> (&str, Option<usize>) {
+    if let Some(stripped) = dataref.strip_suffix(']') {
+        if let Some((name, index)) = stripped.rsplit_once('[') {
+            if let Ok(index) = index.parse() {
+                return (name, Some(index));
+            }
+        }
+    }
+    (dataref, None)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    entries: BTreeMap<String, CatalogEntry>,
+}
+
+impl Catalog {
+    /// Parses the contents of `DataRefs.txt`, lines that are not a dataref such as the header are skipped,
+    /// as are blank lines and `#` comments.
+    ///
+    /// Fails with `InvalidData` if no dataref was found at all.
+    pub fn parse(contents: &str) -> io::Result<Catalog> {
+        Ok(Catalog { entries: catalog_file::parse(contents, "dataref")? })
+    }
+
+    /// Reads and parses a `DataRefs.txt` file
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Catalog> {
+        Catalog::parse(&catalog_file::read(path)?)
+    }
+
+    /// Entry of a dataref, array elements such as `name[3]` resolve to their array
+    /// as long as the index is in bounds
+    pub fn get(&self, dataref: &str) -> Option<&CatalogEntry> {
+        if let Some(entry) = self.entries.get(dataref) {
+            return Some(entry);
+        }
+        let (name, index) = split_index(dataref);
+        let entry = self.entries.get(name)?;
+        match (index, entry.array_len) {
+            (Some(index), Some(len)) if index < len => Some(entry),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, dataref: &str) -> bool {
+        self.get(dataref).is_some()
+    }
+
+    /// Type to subscribe `dataref` with, `None` if it is unknown
+    pub fn infer_type(&self, dataref: &str) -> Option<DataRefType> {
+        self.get(dataref)?.infer_type()
+    }
+
+    /// Entries whose name starts with `prefix`, in alphabetical order
+    pub fn search<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a CatalogEntry> + 'a {
+        catalog_file::search(&self.entries, prefix)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CatalogEntry> {
+        self.entries.values()
+    }
+
+    pub fn len(&self) -> usize { self.entries.len() }
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATAREFS: &str = "\
+2 1200 Thu Jun 26 10:00:00 2025 1200
+# Aircraft specific datarefs are added below
+sim/cockpit2/gauges/indicators/altitude_ft_pilot\tfloat\tn\tfeet\tIndicated altitude
+sim/cockpit2/switches/landing_lights_on\tint\ty\tboolean
+sim/flightmodel/engine/ENGN_thro\tfloat[16]\ty\tratio\tThrottle per engine
+sim/flightmodel2/wing/flap1_deg\tfloat[4][2]\tn\tdegrees
+sim/time/total_running_time_sec\tdouble\tn\t[seconds]
+sim/aircraft/view/acf_ICAO\tbyte[40]\tn\tstring\tICAO code
+sim/cockpit/autopilot/altitude\tint|float\ty\t???
+
+sim/broken/writability\tfloat\tmaybe\tfeet
+sim/broken/dimension\tfloat[x]\tn\tfeet
+sim/broken/missing_writability\tfloat
+no_slash_in_name\tfloat\tn\tfeet
+";
+
+    fn catalog() -> Catalog {
+        Catalog::parse(DATAREFS).unwrap()
+    }
+
+    #[test]
+    fn skips_header_comments_and_malformed_lines() {
+        let catalog = catalog();
+        assert_eq!(catalog.len(), 7);
+        assert!(!catalog.contains("sim/broken/writability"));
+        assert!(!catalog.contains("sim/broken/dimension"));
+        assert!(!catalog.contains("sim/broken/missing_writability"));
+        assert!(!catalog.contains("no_slash_in_name"));
+    }
+
+    #[test]
+    fn parses_all_columns() {
+        let catalog = catalog();
+        let entry = catalog.get("sim/flightmodel/engine/ENGN_thro").unwrap();
+        assert_eq!(entry.get_value_type(), "float");
+        assert_eq!(entry.get_array_len(), Some(16));
+        assert!(entry.is_writable());
+        assert_eq!(entry.get_units(), Some("ratio"));
+        assert_eq!(entry.get_description(), Some("Throttle per engine"));
+
+        let entry = catalog.get("sim/time/total_running_time_sec").unwrap();
+        assert_eq!(entry.get_units(), Some("seconds"));
+        assert_eq!(entry.get_description(), None);
+        assert_eq!(catalog.get("sim/cockpit/autopilot/altitude").unwrap().get_units(), None);
+    }
+
+    #[test]
+    fn multiplies_array_dimensions() {
+        assert_eq!(catalog().get("sim/flightmodel2/wing/flap1_deg").unwrap().get_array_len(), Some(8));
+    }
+
+    #[test]
+    fn array_elements_resolve_within_bounds() {
+        let catalog = catalog();
+        assert!(catalog.contains("sim/flightmodel/engine/ENGN_thro[0]"));
+        assert!(catalog.contains("sim/flightmodel/engine/ENGN_thro[15]"));
+        assert!(!catalog.contains("sim/flightmodel/engine/ENGN_thro[16]"));
+        assert!(!catalog.contains("sim/cockpit2/gauges/indicators/altitude_ft_pilot[0]"));
+    }
+
+    #[test]
+    fn infers_types() {
+        let catalog = catalog();
+        assert_eq!(catalog.infer_type("sim/cockpit2/gauges/indicators/altitude_ft_pilot"), Some(DataRefType::Float));
+        assert_eq!(catalog.infer_type("sim/cockpit2/switches/landing_lights_on"), Some(DataRefType::Bool));
+        assert_eq!(catalog.infer_type("sim/time/total_running_time_sec"), Some(DataRefType::Double));
+        assert_eq!(catalog.infer_type("sim/aircraft/view/acf_ICAO[3]"), Some(DataRefType::Byte));
+        assert_eq!(catalog.infer_type("sim/cockpit/autopilot/altitude"), Some(DataRefType::Int));
+        assert_eq!(catalog.infer_type("sim/unknown"), None);
+    }
+
+    #[test]
+    fn converts_known_units() {
+        let catalog = catalog();
+        assert_eq!(catalog.get("sim/cockpit2/gauges/indicators/altitude_ft_pilot").unwrap().get_unit(), Some(Unit::Feet));
+        assert_eq!(catalog.get("sim/flightmodel/engine/ENGN_thro").unwrap().get_unit(), None);
+    }
+
+    #[test]
+    fn searches_by_prefix_in_order() {
+        let catalog = catalog();
+        let names: Vec<&str> = catalog.search("sim/flightmodel").map(|e| e.get_name()).collect();
+        assert_eq!(names, vec!["sim/flightmodel/engine/ENGN_thro", "sim/flightmodel2/wing/flap1_deg"]);
+        assert_eq!(catalog.search("sim/nothing").count(), 0);
+    }
+
+    #[test]
+    fn rejects_catalog_without_datarefs() {
+        let error = Catalog::parse("2 1200 header only\n# and a comment\n").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,55 @@
+//! Line based catalog files, shared by [`Catalog`](crate::catalog::Catalog)
+//! and [`CommandCatalog`](crate::command_catalog::CommandCatalog).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::ops::Bound;
+use std::path::Path;
+
+use log::debug;
+
+/// Entry described by a single line of a catalog file
+pub(crate) trait CatalogLine: Sized {
+    /// `None` for lines that are not an entry, such as headers
+    fn parse(line: &str) -> Option<Self>;
+
+    fn name(&self) -> &str;
+}
+
+/// Parses every line of `contents`, blank lines and `#` comments are skipped.
+///
+/// Fails with `InvalidData` if no entry was found at all, `kind` names the entries in messages.
+pub(crate) fn parse<E: CatalogLine>(contents: &str, kind: &str) -> io::Result<BTreeMap<String, E>> {
+    let mut entries = BTreeMap::new();
+    for (number, line) in contents.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        match E::parse(line) {
+            Some(entry) => {
+                entries.insert(entry.name().to_string(), entry);
+            }
+            None => debug!("Skipping line {} of the {} catalog: {}", number + 1, kind, line),
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("No {}s found in the catalog", kind)));
+    }
+    Ok(entries)
+}
+
+/// Reads a catalog file, X-Plane does not guarantee them to be valid UTF-8
+pub(crate) fn read<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let contents = fs::read(path)?;
+    Ok(String::from_utf8_lossy(&contents).into_owned())
+}
+
+/// Entries whose name starts with `prefix`, in alphabetical order
+pub(crate) fn search<'a, E>(entries: &'a BTreeMap<String, E>, prefix: &'a str) -> impl Iterator<Item = &'a E> + 'a {
+    entries.range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+        .take_while(move |(name, _)| name.starts_with(prefix))
+        .map(|(_, entry)| entry)
+}
@@ -0,0 +1,37 @@
+//! Operations application code needs from X-Plane, implemented by
+//! [`crate::session::Session`] and, with the `testing` feature, by `testing::MockClient` for tests
+//! without a simulator.
+
+use std::future::Future;
+use std::io;
+
+use crate::command_handler::AlertMessage;
+use crate::dataref::SubscriptionId;
+use crate::dataref_type::{DataRefType, DataRefValueType, FromDataRefValue};
+use crate::error::XPlaneError;
+
+pub trait XPlaneClient {
+    /// Subscribes to a dataref, values are then available through [`XPlaneClient::get_dataref`]
+    fn subscribe(&mut self, dataref: &str, frequency: i32, dataref_type: DataRefType)
+        -> impl Future<Output = io::Result<SubscriptionId>> + Send;
+
+    /// Releases the consumer `id`, the dataref stays subscribed while other consumers remain
+    fn release(&mut self, id: SubscriptionId) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Unsubscribes a dataref for all of its consumers, their ids can no longer be released
+    fn unsubscribe(&mut self, dataref: &str) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Last value of a subscribed dataref, `None` if it is not subscribed
+    fn get_dataref(&self, dataref: &str) -> Option<DataRefValueType>;
+
+    /// Last value of a subscribed dataref read as `V`
+    fn get<V: FromDataRefValue>(&self, dataref: &str) -> Result<V, XPlaneError> {
+        V::from_dataref(dataref, self.get_dataref(dataref).as_ref())
+    }
+
+    fn set_dataref(&self, dataref: &str, value: f32) -> impl Future<Output = io::Result<()>> + Send;
+
+    fn cmd(&self, command: &str) -> impl Future<Output = io::Result<()>> + Send;
+
+    fn alert(&self, message: AlertMessage) -> impl Future<Output = io::Result<()>> + Send;
+}
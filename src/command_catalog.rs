@@ -0,0 +1,182 @@
+//! Command catalog parsed from X-Plane's `Resources/plugins/Commands.txt`
+//! or from the command lists aircraft ship in the same format.
+//!
+//! Every line holds a command name followed by whitespace and an optional description.
+//! Set on a [`Session`](crate::session::Session), unknown commands are rejected
+//! with the closest known names as suggestions.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use crate::catalog_file::{self, CatalogLine};
+use crate::utils::edit_distance;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandEntry {
+    name: String,
+    description: Option<String>,
+}
+
+impl CommandEntry {
+    pub fn get_name(&self) -> &str { &self.name }
+    pub fn get_description(&self) -> Option<&str> { self.description.as_deref() }
+}
+
+impl CatalogLine for CommandEntry {
+    fn parse(line: &str) -> Option<CommandEntry> {
+        let line = line.trim();
+        let (name, description) = match line.split_once(char::is_whitespace) {
+            Some((name, description)) => (name, description.trim()),
+            None => (line, ""),
+        };
+        if !name.contains('/') {
+            return None;
+        }
+        Some(CommandEntry {
+            name: name.to_string(),
+            description: (!description.is_empty()).then(|| description.to_string()),
+        })
+    }
+
+    fn name(&self) -> &str { &self.name }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CommandCatalog {
+    entries: BTreeMap<String, CommandEntry>,
+}
+
+impl CommandCatalog {
+    /// Parses the contents of `Commands.txt`, lines that are not a command are skipped,
+    /// as are blank lines and `#` comments.
+    ///
+    /// Fails with `InvalidData` if no command was found at all.
+    pub fn parse(contents: &str) -> io::Result<CommandCatalog> {
+        Ok(CommandCatalog { entries: catalog_file::parse(contents, "command")? })
+    }
+
+    /// Reads and parses a `Commands.txt` file or an aircraft command list
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<CommandCatalog> {
+        CommandCatalog::parse(&catalog_file::read(path)?)
+    }
+
+    /// Adds the commands of `other`, e.g. an aircraft list on top of `Commands.txt`.
+    ///
+    /// Descriptions of `other` win for commands listed in both.
+    pub fn merge(&mut self, other: CommandCatalog) {
+        self.entries.extend(other.entries);
+    }
+
+    pub fn get(&self, command: &str) -> Option<&CommandEntry> {
+        self.entries.get(command)
+    }
+
+    pub fn contains(&self, command: &str) -> bool {
+        self.entries.contains_key(command)
+    }
+
+    /// Up to `count` known commands closest to `command` by edit distance,
+    /// closest first and alphabetical among equally close ones
+    pub fn suggest(&self, command: &str, count: usize) -> Vec<&CommandEntry> {
+        let mut ranked: Vec<(usize, &CommandEntry)> = self.entries.values()
+            .map(|e| (edit_distance(command, &e.name), e))
+            .collect();
+        ranked.sort_by_key(|(distance, _)| *distance);
+        ranked.into_iter().take(count).map(|(_, e)| e).collect()
+    }
+
+    /// Entries whose name starts with `prefix`, in alphabetical order
+    pub fn search<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a CommandEntry> + 'a {
+        catalog_file::search(&self.entries, prefix)
+    }
+
+    /// Entries containing `text` in their name or description, ignoring case
+    pub fn find<'a>(&'a self, text: &str) -> impl Iterator<Item = &'a CommandEntry> + 'a {
+        let text = text.to_lowercase();
+        self.entries.values().filter(move |e| {
+            e.name.to_lowercase().contains(&text)
+                || e.description.as_ref().is_some_and(|d| d.to_lowercase().contains(&text))
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CommandEntry> {
+        self.entries.values()
+    }
+
+    pub fn len(&self) -> usize { self.entries.len() }
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMANDS: &str = "\
+# Commands of the autopilot and the gear
+sim/autopilot/servos_toggle        Toggle servos.
+sim/autopilot/servos_on            Servos on.
+sim/autopilot/servos_off_any       Disconnect autopilot.
+sim/flight_controls/landing_gear_toggle    Landing gear toggle.
+sim/flight_controls/landing_gear_down
+   sim/flight_controls/flaps_down   Flaps down a notch.
+
+Commands.txt generated by X-Plane 12
+";
+
+    fn catalog() -> CommandCatalog {
+        CommandCatalog::parse(COMMANDS).unwrap()
+    }
+
+    #[test]
+    fn skips_comments_and_lines_without_command() {
+        let catalog = catalog();
+        assert_eq!(catalog.len(), 6);
+        assert!(!catalog.contains("Commands.txt"));
+        assert!(!catalog.contains("#"));
+    }
+
+    #[test]
+    fn parses_optional_description() {
+        let catalog = catalog();
+        assert_eq!(catalog.get("sim/autopilot/servos_off_any").unwrap().get_description(), Some("Disconnect autopilot."));
+        assert_eq!(catalog.get("sim/flight_controls/landing_gear_down").unwrap().get_description(), None);
+        assert_eq!(catalog.get("sim/flight_controls/flaps_down").unwrap().get_description(), Some("Flaps down a notch."));
+    }
+
+    #[test]
+    fn merge_prefers_descriptions_of_other() {
+        let mut catalog = catalog();
+        catalog.merge(CommandCatalog::parse("sim/autopilot/servos_on Engage\nlaminar/B738/autopilot/cmd_a_press").unwrap());
+        assert_eq!(catalog.len(), 7);
+        assert_eq!(catalog.get("sim/autopilot/servos_on").unwrap().get_description(), Some("Engage"));
+    }
+
+    #[test]
+    fn suggests_closest_commands_first() {
+        let catalog = catalog();
+        let suggestions: Vec<&str> = catalog.suggest("sim/autopilot/servo_toggle", 2).iter().map(|e| e.get_name()).collect();
+        assert_eq!(suggestions, vec!["sim/autopilot/servos_toggle", "sim/autopilot/servos_on"]);
+    }
+
+    #[test]
+    fn suggestions_with_equal_distance_are_alphabetical() {
+        let catalog = CommandCatalog::parse("sim/b/cmd\nsim/a/cmd\nsim/c/cmd").unwrap();
+        let suggestions: Vec<&str> = catalog.suggest("sim/x/cmd", 3).iter().map(|e| e.get_name()).collect();
+        assert_eq!(suggestions, vec!["sim/a/cmd", "sim/b/cmd", "sim/c/cmd"]);
+    }
+
+    #[test]
+    fn searches_by_prefix_and_text() {
+        let catalog = catalog();
+        assert_eq!(catalog.search("sim/autopilot/servos_o").count(), 2);
+        let found: Vec<&str> = catalog.find("GEAR").map(|e| e.get_name()).collect();
+        assert_eq!(found, vec!["sim/flight_controls/landing_gear_down", "sim/flight_controls/landing_gear_toggle"]);
+        assert_eq!(catalog.find("disconnect").count(), 1);
+    }
+
+    #[test]
+    fn rejects_list_without_commands() {
+        assert_eq!(CommandCatalog::parse("# nothing here\n\n").unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,12 +1,25 @@
 use std::io;
 use std::net::{SocketAddr};
+use std::sync::Arc;
 use log::{debug};
-use tokio::net::UdpSocket;
 
-use crate::consts::ALRT_PREFIX;
+use crate::command_catalog::CommandCatalog;
+use crate::transport::Transport;
+
+use crate::consts::{ALRT_PREFIX, XP_COMMAND_SUGGESTIONS};
 
 // TODO: better alert system
-#[derive(Debug, Default)]
+/// Up to four lines shown by X-Plane in an alert window.
+///
+/// With the `serde` feature, it serializes as its lines, deserializing rejects lines
+/// [`AlertMessage::set_line`] would reject:
+///
+/// ```json
+/// {"lines": ["Autopilot disconnected", "", "", ""]}
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "AlertLines"))]
 pub struct AlertMessage {
     lines: [String; 4],
 }
@@ -24,20 +37,78 @@ impl AlertMessage {
         self.lines[index] = line.to_string();
         Ok(())
     }
+
+    pub fn get_line(&self, index: usize) -> Option<&str> {
+        self.lines.get(index).map(|e| e.as_str())
+    }
+}
+
+/// Unchecked serialized form of [`AlertMessage`]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct AlertLines {
+    lines: [String; 4],
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<AlertLines> for AlertMessage {
+    type Error = io::Error;
+
+    fn try_from(value: AlertLines) -> Result<Self, Self::Error> {
+        let mut message = AlertMessage::default();
+        for (index, line) in value.lines.iter().enumerate() {
+            message.set_line(line, index)?;
+        }
+        Ok(message)
+    }
 }
 
 #[derive(Default)]
-pub struct CommandHandler {}
+pub struct CommandHandler {
+    /// Validates commands when set
+    catalog: Option<Arc<CommandCatalog>>,
+}
 
 impl CommandHandler {
+    /// Validates commands against `catalog`, `None` turns validation off
+    pub fn set_catalog(&mut self, catalog: Option<Arc<CommandCatalog>>) {
+        self.catalog = catalog;
+    }
+
+    pub fn get_catalog(&self) -> Option<&Arc<CommandCatalog>> {
+        self.catalog.as_ref()
+    }
+
+    /// Fails with `NotFound` if a catalog is set and does not list `command`,
+    /// naming the closest known commands
+    fn validate(&self, command: &str) -> io::Result<()> {
+        let catalog = match &self.catalog {
+            Some(e) => e,
+            None => return Ok(()),
+        };
+        if catalog.contains(command) {
+            return Ok(());
+        }
+        let suggestions: Vec<&str> = catalog.suggest(command, XP_COMMAND_SUGGESTIONS).iter()
+            .map(|e| e.get_name())
+            .collect();
+        let message = if suggestions.is_empty() {
+            format!("Unknown command {}", command)
+        } else {
+            format!("Unknown command {}, did you mean {}?", command, suggestions.join(", "))
+        };
+        Err(io::Error::new(io::ErrorKind::NotFound, message))
+    }
+
     fn cmd_message(&self, command: &str) -> String {
         format!("CMND\0{}\0", command)
     }
 
-    pub async fn send_command(&self, command: &str, sending_socket: &UdpSocket, receiving_address: &SocketAddr) -> io::Result<()> {
+    pub async fn send_command<S: Transport>(&self, command: &str, sending_socket: &S, receiving_address: &SocketAddr) -> io::Result<()> {
+        self.validate(command)?;
         debug!("Sending command {}", command);
         let message = self.cmd_message(command);
-        sending_socket.send_to(message.as_bytes(), receiving_address).await?;
+        sending_socket.send_to(message.as_bytes(), *receiving_address).await?;
         Ok(())
     }
 
@@ -67,10 +138,33 @@ impl CommandHandler {
         message
     }
 
-    pub async fn alert(&self, alert_message: AlertMessage, sending_socket: &UdpSocket, receiving_address: &SocketAddr) -> io::Result<()> {
+    pub async fn alert<S: Transport>(&self, alert_message: AlertMessage, sending_socket: &S, receiving_address: &SocketAddr) -> io::Result<()> {
         debug!("Sending alert");
         let message = self.alert_message(alert_message);
-        sending_socket.send_to(message.as_slice(), receiving_address).await?;
+        sending_socket.send_to(message.as_slice(), *receiving_address).await?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alert_round_trips_in_documented_format() {
+        let json = r#"{"lines":["Autopilot disconnected","","",""]}"#;
+        let mut alert = AlertMessage::default();
+        alert.set_line("Autopilot disconnected", 0).unwrap();
+        assert_eq!(serde_json::to_string(&alert).unwrap(), json);
+        assert_eq!(serde_json::from_str::<AlertMessage>(json).unwrap(), alert);
+    }
+
+    #[test]
+    fn alert_rejects_too_long_lines() {
+        let json = format!(r#"{{"lines":["", "{}", "", ""]}}"#, "x".repeat(241));
+        assert!(serde_json::from_str::<AlertMessage>(&json).is_err());
+
+        let json = format!(r#"{{"lines":["", "{}", "", ""]}}"#, "x".repeat(240));
+        assert!(serde_json::from_str::<AlertMessage>(&json).is_ok());
+    }
+}
@@ -21,9 +21,68 @@ pub const XP_MULTICAST_PARSE_MAX_TRIES: i32 = 3;
 pub const XP_DEFAULT_RECEIVING_PORT: u16 = 49000;
 pub const XP_DEFAULT_SENDING_PORT: u16 = 49001;
 
+// ─── Backends ────────────────────────────────────────────────────────────────
+/// Read timeout in milliseconds of the std backend receiver thread, bounds the time to stop it
+pub const XP_STD_RECEIVE_POLL_MS: u64 = 100;
+
+// ─── Subscriptions ───────────────────────────────────────────────────────────
+/// Time in milliseconds for X-Plane to answer a new RREF subscription before it is considered dead
+pub const XP_SUBSCRIPTION_CONFIRM_TIMEOUT_MS: u64 = 2000;
+
+/// Upper bound of samples kept in the history of a single dataref
+pub const XP_HISTORY_MAX_DEPTH: usize = 4096;
+
+/// Pause in milliseconds between consecutive RREF requests of a batch
+pub const XP_BATCH_SEND_INTERVAL_MS: u64 = 5;
+
+/// Number of times a batch resends the subscriptions X-Plane did not answer
+pub const XP_BATCH_MAX_RETRIES: u32 = 2;
+
+/// Time in milliseconds before a released RREF index is handed out again
+pub const XP_INDEX_QUARANTINE_MS: u64 = 2000;
+
+// ─── Values ──────────────────────────────────────────────────────────────────
+/// Raw values above this are read as `true` by [`DataRefType::Bool`](crate::dataref_type::DataRefType::Bool)
+pub const XP_BOOL_THRESHOLD: f32 = 0.5;
+
+// ─── Catalogs ────────────────────────────────────────────────────────────────
+/// Number of close matches suggested for an unknown command
+pub const XP_COMMAND_SUGGESTIONS: usize = 3;
+
+/// Frequency of profile fields that do not set one
+pub const XP_PROFILE_DEFAULT_FREQUENCY: i32 = 5;
+
+// ─── Aircraft detection ──────────────────────────────────────────────────────
+/// String dataref holding the ICAO type code of the loaded aircraft
+pub const XP_AIRCRAFT_ICAO_DATAREF: &str = "sim/aircraft/view/acf_ICAO";
+
+/// Bytes of the ICAO type code read, one RREF subscription each
+pub const XP_AIRCRAFT_ICAO_LEN: usize = 8;
+
+/// String dataref holding the `.acf` path of the loaded aircraft, relative to the X-Plane folder
+pub const XP_AIRCRAFT_PATH_DATAREF: &str = "sim/aircraft/view/acf_relative_path";
+
+/// Bytes of the `.acf` path read, longer paths are truncated
+pub const XP_AIRCRAFT_PATH_LEN: usize = 64;
+
+/// Frequency the aircraft identity is requested at
+pub const XP_AIRCRAFT_FREQUENCY: i32 = 1;
+
+/// Time in milliseconds a changed aircraft identity must stay the same before profiles are switched
+pub const XP_AIRCRAFT_SETTLE_MS: u64 = 1500;
+
+// ─── Session ─────────────────────────────────────────────────────────────────
+/// Time in milliseconds a graceful shutdown waits for the unsubscribe requests to be sent
+pub const XP_SHUTDOWN_TIMEOUT_MS: u64 = 1000;
+
+// ─── Testing ─────────────────────────────────────────────────────────────────
+/// Resolution in milliseconds of the RREF scheduler of the fake X-Plane
+pub const XP_FAKE_TICK_MS: u64 = 5;
+
 // ─── Message constants ───────────────────────────────────────────────────────
 pub const STANDARD_BUFFER_SIZE: usize = 1024;
 pub const BEACON_PREFIX: &[u8; 4] = b"BECN";
 pub const RREF_PREFIX: &[u8; 4] = b"RREF";
 pub const CMND_PREFIX: &[u8; 4] = b"CMND";
 pub const ALRT_PREFIX: &[u8; 4] = b"ALRT";
+pub const DREF_PREFIX: &[u8; 4] = b"DREF";
@@ -1,13 +1,85 @@
-use crate::consts::RREF_PREFIX;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::consts::{RREF_PREFIX, XP_HISTORY_MAX_DEPTH};
+use crate::dataref_handle::DataRefSlot;
 use crate::dataref_type::{DataRefType, DataRefValueType};
+use crate::units::Unit;
+
+/// State of an RREF subscription, as observed from the replies of X-Plane.
+///
+/// X-Plane silently ignores requests for datarefs that do not exist,
+/// so the only way to tell a typo from a slow simulator is to wait for the index to show up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionStatus {
+    /// Subscription was sent, no reply for its index has arrived yet
+    Pending,
+    /// At least one value for the index was received
+    Active,
+    /// No reply arrived within the confirmation timeout
+    NoResponse,
+}
+
+/// Identifies one consumer of a shared dataref subscription
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(pub(crate) u64);
+
+/// Receive statistics of a dataref
+#[derive(Debug, Clone, Copy)]
+pub struct DataRefMeta {
+    last_update: Option<Instant>,
+    update_count: u64,
+    interval: Option<Duration>,
+}
+
+impl DataRefMeta {
+    /// Instant the last value was received
+    pub fn get_last_update(&self) -> Option<Instant> { self.last_update }
+    /// Number of values received since the subscription
+    pub fn get_update_count(&self) -> u64 { self.update_count }
+    /// Smoothed time between two received values
+    pub fn get_interval(&self) -> Option<Duration> { self.interval }
+    /// Time since the last value was received, `None` if no value has arrived yet
+    pub fn age(&self) -> Option<Duration> { self.last_update.map(|e| e.elapsed()) }
+
+    /// Whether the last value is older than `max_age`, or no value has arrived at all
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        match self.age() {
+            Some(age) => age > max_age,
+            None => true,
+        }
+    }
+}
+
+/// Frequency and history depth requested by one consumer
+struct Consumer {
+    id: SubscriptionId,
+    freq: i32,
+    history_depth: usize,
+}
 
 pub struct DataRef {
     name: String,
     index: i32,
+    /// Frequency requested from X-Plane, the highest one of all consumers
     freq: i32,
+    consumers: Vec<Consumer>,
 
     value_type: DataRefType,
+    /// Unit X-Plane sends the value in, if declared
+    unit: Option<Unit>,
     raw: Option<f32>,
+    /// Copy of `raw` read by handles without going through the dataref table
+    slot: Arc<DataRefSlot>,
+
+    meta: DataRefMeta,
+    /// Last received values, only kept when a history depth was set
+    history: VecDeque<(Instant, f32)>,
+    /// Number of values kept, the highest depth of all consumers
+    history_depth: usize,
+
+    status: SubscriptionStatus,
+    subscribed_at: Instant,
 }
 
 impl DataRef {
@@ -17,8 +89,20 @@ impl DataRef {
             name,
             index,
             value_type,
+            unit: None,
             freq: frequency,
+            consumers: Vec::new(),
             raw: None,
+            slot: Arc::new(DataRefSlot::default()),
+            meta: DataRefMeta {
+                last_update: None,
+                update_count: 0,
+                interval: None,
+            },
+            history: VecDeque::new(),
+            history_depth: 0,
+            status: SubscriptionStatus::Pending,
+            subscribed_at: Instant::now(),
         }
     }
 
@@ -27,19 +111,119 @@ impl DataRef {
     }
 
     pub fn get(&self) -> DataRefValueType {
-        let val: f32 = match self.raw {
-            None => return DataRefValueType::Unknown,
-            Some(v) => v
-        };
-        match self.value_type {
-            DataRefType::Float => DataRefValueType::Float(val),
-            DataRefType::Int => DataRefValueType::Int(val as i32),
-            DataRefType::Char => DataRefValueType::Char(val as u8 as char),
+        match self.raw {
+            None => DataRefValueType::Unknown,
+            Some(v) => self.value_type.convert(v),
         }
     }
 
     pub fn update(&mut self, value: f32) {
+        let now = Instant::now();
+        if let Some(last_update) = self.meta.last_update {
+            // Exponential moving average, so a single late packet does not skew the interval
+            let sample = now - last_update;
+            self.meta.interval = Some(match self.meta.interval {
+                Some(interval) => interval * 7 / 8 + sample / 8,
+                None => sample,
+            });
+        }
+        self.meta.last_update = Some(now);
+        self.meta.update_count += 1;
+
+        if self.history_depth > 0 {
+            // Capacity is reserved up front, so this never allocates on the receiving thread
+            if self.history.len() == self.history_depth {
+                self.history.pop_front();
+            }
+            self.history.push_back((now, value));
+        }
+
         self.raw = Some(value);
+        self.slot.store(value);
+        self.status = SubscriptionStatus::Active;
+    }
+
+    /// Status of the subscription, a pending subscription turns into
+    /// [`SubscriptionStatus::NoResponse`] once `timeout` has passed since it was sent.
+    pub fn status(&mut self, timeout: Duration) -> SubscriptionStatus {
+        if self.status == SubscriptionStatus::Pending && self.subscribed_at.elapsed() >= timeout {
+            self.status = SubscriptionStatus::NoResponse;
+        }
+        self.status
+    }
+
+    /// Sets the number of samples consumer `id` wants kept in the history, 0 for none.
+    ///
+    /// The history keeps the highest depth any consumer asked for, returns `false`
+    /// if `id` is not a consumer of this dataref.
+    pub fn set_history_depth(&mut self, id: SubscriptionId, depth: usize) -> bool {
+        match self.consumers.iter_mut().find(|e| e.id == id) {
+            Some(consumer) => consumer.history_depth = depth,
+            None => return false,
+        }
+        self.resize_history(self.max_consumer_history_depth());
+        true
+    }
+
+    /// Shrinking the depth drops the oldest samples
+    fn resize_history(&mut self, depth: usize) {
+        let depth = depth.min(XP_HISTORY_MAX_DEPTH);
+        if depth == self.history_depth {
+            return;
+        }
+        while self.history.len() > depth {
+            self.history.pop_front();
+        }
+        if depth == 0 {
+            self.history = VecDeque::new();
+        } else {
+            self.history.reserve_exact(depth - self.history.len());
+        }
+        self.history_depth = depth;
+    }
+
+    pub fn set_unit(&mut self, unit: Option<Unit>) {
+        self.unit = unit;
+    }
+
+    /// Received values from the oldest to the newest, with the instant they arrived
+    pub fn history(&self) -> impl Iterator<Item = (Instant, DataRefValueType)> + '_ {
+        self.history.iter().map(|(at, raw)| (*at, self.value_type.convert(*raw)))
+    }
+
+    /// Restarts the confirmation window after the subscription was sent to X-Plane again
+    pub fn mark_resubscribed(&mut self) {
+        if self.status == SubscriptionStatus::NoResponse {
+            self.status = SubscriptionStatus::Pending;
+        }
+        self.subscribed_at = Instant::now();
+    }
+
+    /// Adds a consumer requesting `frequency`, the dataref is then sent at the highest requested frequency
+    pub fn add_consumer(&mut self, id: SubscriptionId, frequency: i32) {
+        self.consumers.push(Consumer { id, freq: frequency, history_depth: 0 });
+        self.freq = self.max_consumer_freq();
+    }
+
+    /// Removes a consumer, returns `false` if it was not registered for this dataref
+    pub fn remove_consumer(&mut self, id: SubscriptionId) -> bool {
+        let len = self.consumers.len();
+        self.consumers.retain(|e| e.id != id);
+        if self.consumers.is_empty() {
+            return len != 0;
+        }
+
+        self.freq = self.max_consumer_freq();
+        self.resize_history(self.max_consumer_history_depth());
+        len != self.consumers.len()
+    }
+
+    fn max_consumer_freq(&self) -> i32 {
+        self.consumers.iter().map(|e| e.freq).max().unwrap_or(self.freq)
+    }
+
+    fn max_consumer_history_depth(&self) -> usize {
+        self.consumers.iter().map(|e| e.history_depth).max().unwrap_or(0)
     }
 
     pub fn subscription_message(&self) -> Vec<u8> {
@@ -70,6 +254,8 @@ impl DataRef {
 
     pub fn unsubscribe_message(&mut self) -> Vec<u8> {
         self.freq = 0;
+        // Handles outliving the subscription must not keep showing the last value
+        self.slot.clear();
         self.subscription_message()
     }
 
@@ -77,4 +263,62 @@ impl DataRef {
     pub fn get_index(&self) -> i32 { self.index }
     pub fn get_freq(&self) -> i32 { self.freq }
     pub fn get_value_type(&self) -> &DataRefType { &self.value_type }
-}
\ No newline at end of file
+    pub fn get_unit(&self) -> Option<Unit> { self.unit }
+    pub fn get_meta(&self) -> DataRefMeta { self.meta }
+    pub(crate) fn get_slot(&self) -> Arc<DataRefSlot> { self.slot.clone() }
+    pub fn get_history_depth(&self) -> usize { self.history_depth }
+    pub fn get_consumers(&self) -> impl Iterator<Item = SubscriptionId> + '_ { self.consumers.iter().map(|e| e.id) }
+    pub fn get_consumer_count(&self) -> usize { self.consumers.len() }
+    pub fn get_subscribed_at(&self) -> Instant { self.subscribed_at }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataref() -> DataRef {
+        DataRef::new("sim/flightmodel/position/elevation", 1, 5, DataRefType::Float)
+    }
+
+    #[test]
+    fn history_keeps_the_deepest_consumer_request() {
+        let mut dataref = dataref();
+        dataref.add_consumer(SubscriptionId(1), 5);
+        dataref.add_consumer(SubscriptionId(2), 5);
+        assert!(dataref.set_history_depth(SubscriptionId(1), 4));
+        for value in 0..6 {
+            dataref.update(value as f32);
+        }
+
+        assert!(dataref.set_history_depth(SubscriptionId(2), 2));
+        assert_eq!(dataref.get_history_depth(), 4);
+        assert!(dataref.set_history_depth(SubscriptionId(2), 0));
+        assert_eq!(dataref.get_history_depth(), 4);
+
+        let values: Vec<_> = dataref.history().map(|(_, value)| value).collect();
+        assert_eq!(values, (2..6).map(|e| DataRefValueType::Float(e as f32)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn history_shrinks_when_the_deepest_consumer_leaves() {
+        let mut dataref = dataref();
+        dataref.add_consumer(SubscriptionId(1), 5);
+        dataref.add_consumer(SubscriptionId(2), 5);
+        dataref.set_history_depth(SubscriptionId(1), 4);
+        dataref.set_history_depth(SubscriptionId(2), 2);
+        for value in 0..6 {
+            dataref.update(value as f32);
+        }
+
+        dataref.remove_consumer(SubscriptionId(1));
+        assert_eq!(dataref.get_history_depth(), 2);
+        assert_eq!(dataref.history().count(), 2);
+    }
+
+    #[test]
+    fn history_depth_of_unknown_consumer_is_rejected() {
+        let mut dataref = dataref();
+        dataref.add_consumer(SubscriptionId(1), 5);
+        assert!(!dataref.set_history_depth(SubscriptionId(2), 4));
+        assert_eq!(dataref.get_history_depth(), 0);
+    }
+}
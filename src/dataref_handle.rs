@@ -0,0 +1,111 @@
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::dataref::SubscriptionId;
+use crate::dataref_type::{raw_to_bool, raw_to_byte, raw_to_char, raw_to_double, raw_to_int, DataRefType};
+
+/// Marks that the slot holds a value, the raw f32 bits live in the lower half
+const SLOT_SET: u64 = 1 << 32;
+
+/// Latest raw value of a dataref, shared lock-free between the receiving thread and handles
+#[derive(Debug, Default)]
+pub(crate) struct DataRefSlot {
+    value: AtomicU64,
+}
+
+impl DataRefSlot {
+    pub(crate) fn store(&self, raw: f32) {
+        self.value.store(SLOT_SET | raw.to_bits() as u64, Ordering::Release);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.value.store(0, Ordering::Release);
+    }
+
+    pub(crate) fn load(&self) -> Option<f32> {
+        let value = self.value.load(Ordering::Acquire);
+        if value & SLOT_SET == 0 {
+            return None;
+        }
+        Some(f32::from_bits(value as u32))
+    }
+}
+
+/// Rust type a raw RREF value can be read as through a [`DataRefHandle`]
+pub trait DataRefScalar: Sized {
+    /// Type the dataref is subscribed as
+    const TYPE: DataRefType;
+
+    fn from_raw(raw: f32) -> Self;
+}
+
+impl DataRefScalar for f32 {
+    const TYPE: DataRefType = DataRefType::Float;
+    fn from_raw(raw: f32) -> Self { raw }
+}
+
+impl DataRefScalar for i32 {
+    const TYPE: DataRefType = DataRefType::Int;
+    fn from_raw(raw: f32) -> Self { raw_to_int(raw) }
+}
+
+impl DataRefScalar for char {
+    const TYPE: DataRefType = DataRefType::Char;
+    fn from_raw(raw: f32) -> Self { raw_to_char(raw) }
+}
+
+impl DataRefScalar for bool {
+    const TYPE: DataRefType = DataRefType::Bool;
+    fn from_raw(raw: f32) -> Self { raw_to_bool(raw) }
+}
+
+impl DataRefScalar for f64 {
+    const TYPE: DataRefType = DataRefType::Double;
+    fn from_raw(raw: f32) -> Self { raw_to_double(raw) }
+}
+
+impl DataRefScalar for u8 {
+    const TYPE: DataRefType = DataRefType::Byte;
+    fn from_raw(raw: f32) -> Self { raw_to_byte(raw) }
+}
+
+/// Typed view of a subscribed dataref.
+///
+/// Reading goes straight to the value slot of the dataref,
+/// without hashing the name or locking the dataref table.
+/// The handle is a consumer of the subscription, pass [`DataRefHandle::id`] to `Session::release` when done.
+pub struct DataRefHandle<T: DataRefScalar> {
+    id: SubscriptionId,
+    slot: Arc<DataRefSlot>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T: DataRefScalar> DataRefHandle<T> {
+    pub(crate) fn new(id: SubscriptionId, slot: Arc<DataRefSlot>) -> Self {
+        DataRefHandle {
+            id,
+            slot,
+            _type: PhantomData,
+        }
+    }
+
+    /// Latest value, `None` until X-Plane sends one or after the dataref was unsubscribed
+    pub fn get(&self) -> Option<T> {
+        self.slot.load().map(T::from_raw)
+    }
+
+    pub fn get_raw(&self) -> Option<f32> {
+        self.slot.load()
+    }
+
+    pub fn id(&self) -> SubscriptionId {
+        self.id
+    }
+}
+
+impl<T: DataRefScalar> Clone for DataRefHandle<T> {
+    fn clone(&self) -> Self {
+        DataRefHandle::new(self.id, self.slot.clone())
+    }
+}
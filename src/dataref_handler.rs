@@ -1,15 +1,25 @@
 use std::io;
 use std::net::{SocketAddr};
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::pin::pin;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use dashmap::DashMap;
-use log::{debug, error, info};
-use tokio::net::UdpSocket;
-use tokio::task;
-use tokio::task::JoinHandle;
-use crate::consts::RREF_PREFIX;
-use crate::dataref::DataRef;
+use log::{debug, error, info, warn};
+use crate::backend::{Backend, CancellationToken};
+use crate::catalog::Catalog;
+use crate::consts::{DREF_PREFIX, RREF_PREFIX, XP_BATCH_MAX_RETRIES, XP_BATCH_SEND_INTERVAL_MS,
+                    XP_INDEX_QUARANTINE_MS, XP_SUBSCRIPTION_CONFIRM_TIMEOUT_MS};
+use crate::dataref_handle::{DataRefHandle, DataRefScalar};
+use crate::dataref::{DataRef, DataRefMeta, SubscriptionId, SubscriptionStatus};
+use crate::index_allocator::IndexAllocator;
+use crate::snapshot::Snapshot;
+use crate::transport::Transport;
+use crate::units::Unit;
 use crate::dataref_type::{DataRefType, DataRefValueType};
 use crate::dataref_handler::MessageStatus::InvalidData;
+use crate::utils::{race, Either, Notifier};
 
 pub enum MessageStatus<T> {
     Ok(T),
@@ -18,27 +28,63 @@ pub enum MessageStatus<T> {
     InvalidData,
 }
 
-pub struct DataRefHandler {
-    index_counter: i32,
+/// Outcome of one dataref in a batch operation
+#[derive(Debug)]
+pub struct BatchResult<T> {
+    dataref: String,
+    attempts: u32,
+    result: io::Result<T>,
+}
+
+impl<T> BatchResult<T> {
+    pub fn get_dataref(&self) -> &str { &self.dataref }
+    /// Number of times the request was sent to X-Plane
+    pub fn get_attempts(&self) -> u32 { self.attempts }
+    pub fn get_result(&self) -> &io::Result<T> { &self.result }
+    pub fn into_result(self) -> io::Result<T> { self.result }
+    pub fn is_ok(&self) -> bool { self.result.is_ok() }
+}
+
+pub struct DataRefHandler<B: Backend> {
+    indexes: IndexAllocator,
     id_datarefs: Arc<DashMap<i32, DataRef>>,
     name_id_map: DashMap<String, i32>,
-
-    handle: Option<JoinHandle<()>>,
+    subscription_counter: u64,
+    subscription_names: DashMap<SubscriptionId, String>,
+
+    /// Woken by the receiving thread after every processed RREF message
+    updated: Arc<Notifier>,
+    /// Held for writing while an RREF message is applied, counts the processed messages
+    frame: Arc<RwLock<u64>>,
+    confirm_timeout: Duration,
+    /// Validates subscriptions and writes when set
+    catalog: Option<Arc<Catalog>>,
+
+    /// Stops the receiving thread, `None` until it was spawned
+    cancel: Option<CancellationToken>,
+    _backend: PhantomData<B>,
 }
 
-impl Default for DataRefHandler {
+impl<B: Backend> Default for DataRefHandler<B> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl DataRefHandler {
+impl<B: Backend> DataRefHandler<B> {
     pub fn new() -> Self {
         DataRefHandler {
-            index_counter: 1,
+            indexes: IndexAllocator::new(Duration::from_millis(XP_INDEX_QUARANTINE_MS)),
             id_datarefs: Arc::new(DashMap::new()),
             name_id_map: DashMap::new(),
-            handle: None,
+            subscription_counter: 1,
+            subscription_names: DashMap::new(),
+            updated: Arc::new(Notifier::default()),
+            frame: Arc::new(RwLock::new(0)),
+            confirm_timeout: Duration::from_millis(XP_SUBSCRIPTION_CONFIRM_TIMEOUT_MS),
+            catalog: None,
+            cancel: None,
+            _backend: PhantomData,
         }
     }
 
@@ -66,12 +112,19 @@ impl DataRefHandler {
         }
     }
 
-    pub fn process_message(map: &mut Arc<DashMap<i32, DataRef>>, data: &[u8]) -> MessageStatus<usize> {
-        let vars_count: usize = match DataRefHandler::should_process(data) {
+    /// Applies all values of an RREF message.
+    ///
+    /// The whole message is applied under the write side of `frame`,
+    /// which also counts the processed messages.
+    pub fn process_message(map: &mut Arc<DashMap<i32, DataRef>>, frame: &RwLock<u64>, data: &[u8]) -> MessageStatus<usize> {
+        let vars_count: usize = match Self::should_process(data) {
             MessageStatus::Ok(e) => e,
             other => return other,
         };
 
+        let mut sequence = frame.write().unwrap_or_else(|e| e.into_inner());
+        *sequence += 1;
+
         for i in 0..vars_count {
             let i_index = 5 + i * 8;
             let v_index = i_index + 4;
@@ -85,90 +138,545 @@ impl DataRefHandler {
         MessageStatus::Ok(vars_count)
     }
 
-    pub fn spawn_run_thread(&mut self, receiving_socket: Arc<UdpSocket>) {
+    pub fn spawn_run_thread<S: Transport>(&mut self, receiving_socket: Arc<S>) {
         info!("Spawning dataref handler thread");
 
-        if self.handle.is_some() {
+        if self.cancel.is_some() {
             error!("Dataref handler thread already running");
             return;
         }
 
         let mut datarefs = self.id_datarefs.clone();
-        let handle = task::spawn(async move {
-            let mut buffer = [0; 4096];
-
-            loop {
-                match receiving_socket.recv(&mut buffer).await {
-                    Ok(received) => {
-                        match DataRefHandler::process_message(&mut datarefs, &buffer[..received]) {
-                            MessageStatus::Ok(count) => {
-                                debug!(
-                                    "Processed RREF message with {} bytes ({} dataref updates)",
-                                    received, count
-                                );
-                            }
-                            MessageStatus::WrongPrefix => {
-                                debug!("Received non-RREF data");
-                            }
-                            MessageStatus::InvalidData | MessageStatus::InvalidLength => {
-                                error!("Failed to process RREF message");
-                                debug!("Received data: {:?}", &buffer[..received]);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        error!("Error receiving dataref: {}", e);
-                    }
+        let updated = self.updated.clone();
+        let frame = self.frame.clone();
+        let cancel = CancellationToken::new();
+        B::spawn_receiver(receiving_socket, cancel.clone(), move |data| {
+            match Self::process_message(&mut datarefs, &frame, data) {
+                MessageStatus::Ok(count) => {
+                    debug!(
+                        "Processed RREF message with {} bytes ({} dataref updates)",
+                        data.len(), count
+                    );
+                    updated.notify_waiters();
+                }
+                MessageStatus::WrongPrefix => {
+                    debug!("Received non-RREF data");
+                }
+                MessageStatus::InvalidData | MessageStatus::InvalidLength => {
+                    error!("Failed to process RREF message");
+                    debug!("Received data: {:?}", data);
                 }
             }
         });
 
-        self.handle = Some(handle);
+        self.cancel = Some(cancel);
     }
 
-    pub async fn new_subscribe(&mut self, name: &str, frequency: i32, dataref_type: DataRefType,
-                               sending_socket: &UdpSocket, receiving_address: &SocketAddr) -> io::Result<()> {
-        // TODO: smarter index counter
-        let index = self.index_counter;
-        self.index_counter += 1;
+    /// Stops the receiving thread, values are no longer updated afterwards
+    pub fn stop_run_thread(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            info!("Stopping dataref handler thread");
+            cancel.cancel();
+        }
+    }
 
-        let dataref = DataRef::new(name, index, frequency, dataref_type);
-        sending_socket.send_to(dataref.subscription_message().as_slice(), receiving_address).await?;
+    /// Subscribes a new consumer of `name`.
+    ///
+    /// Consumers of the same dataref share a single RREF index,
+    /// X-Plane is asked for the highest frequency any of them requested.
+    pub async fn new_subscribe<S: Transport>(&mut self, name: &str, frequency: i32, dataref_type: DataRefType,
+                               sending_socket: &S, receiving_address: &SocketAddr) -> io::Result<SubscriptionId> {
+        let entry = match &self.catalog {
+            Some(catalog) => match catalog.get(name) {
+                Some(e) => Some(e),
+                None => return Err(io::Error::new(io::ErrorKind::NotFound, format!("Unknown dataref {}", name))),
+            },
+            None => None,
+        };
+        let unit = entry.and_then(|e| e.get_unit());
+
+        let id = SubscriptionId(self.subscription_counter);
+        self.subscription_counter += 1;
+
+        let existing = self.name_id_map.get(name).map(|e| *e);
+        let message = match existing {
+            Some(index) => {
+                let mut dataref = match self.id_datarefs.get_mut(&index) {
+                    Some(e) => e,
+                    None => return Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+                };
+                if *dataref.get_value_type() != dataref_type {
+                    warn!("Dataref {} is already subscribed as {:?}, ignoring {:?}",
+                        name, dataref.get_value_type(), dataref_type);
+                }
 
-        self.id_datarefs.insert(dataref.get_index(), dataref);
-        self.name_id_map.insert(name.to_string(), index);
+                let previous_freq = dataref.get_freq();
+                dataref.add_consumer(id, frequency);
+                self.subscription_names.insert(id, name.to_string());
 
-        Ok(())
+                // Only renegotiate when the new consumer is faster than the current rate
+                if dataref.get_freq() == previous_freq {
+                    debug!("Dataref {} shared by {} consumers", name, dataref.get_consumer_count());
+                    return Ok(id);
+                }
+                dataref.subscription_message()
+            }
+            None => {
+                let mut dataref = DataRef::new(name, self.indexes.allocate(), frequency, dataref_type);
+                dataref.set_unit(unit);
+                dataref.add_consumer(id, frequency);
+                let message = dataref.subscription_message();
+
+                // Register before sending, so the first reply is not dropped
+                self.name_id_map.insert(name.to_string(), dataref.get_index());
+                self.id_datarefs.insert(dataref.get_index(), dataref);
+                self.subscription_names.insert(id, name.to_string());
+                message
+            }
+        };
+
+        if let Err(e) = sending_socket.send_to(message.as_slice(), *receiving_address).await {
+            self.rollback_subscribe(id, name, existing.is_none());
+            return Err(e);
+        }
+
+        Ok(id)
     }
 
-    pub async fn unsubscribe(&mut self, dataref: &str,
-                             sending_socket: &UdpSocket, receiving_address: &SocketAddr) -> io::Result<()> {
-        let index = match self.name_id_map.get(dataref) {
+    /// Undoes the bookkeeping of a subscription whose request could not be sent,
+    /// `created` tells whether the dataref was registered for it
+    fn rollback_subscribe(&mut self, id: SubscriptionId, name: &str, created: bool) {
+        self.subscription_names.remove(&id);
+        let index = match self.name_id_map.get(name).map(|e| *e) {
+            Some(e) => e,
+            None => return,
+        };
+        if created {
+            self.name_id_map.remove(name);
+            self.id_datarefs.remove(&index);
+            self.indexes.release(index);
+        } else if let Some(mut dataref) = self.id_datarefs.get_mut(&index) {
+            dataref.remove_consumer(id);
+        }
+    }
+
+    /// Subscribes a new consumer of `name` and returns a typed handle reading its value slot.
+    pub async fn new_subscribe_handle<T: DataRefScalar, S: Transport>(&mut self, name: &str, frequency: i32,
+                                                        sending_socket: &S, receiving_address: &SocketAddr) -> io::Result<DataRefHandle<T>> {
+        let id = self.new_subscribe(name, frequency, T::TYPE, sending_socket, receiving_address).await?;
+
+        let index = match self.name_id_map.get(name) {
             Some(e) => *e,
             None => return Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
         };
+        match self.id_datarefs.get(&index) {
+            Some(e) => Ok(DataRefHandle::new(id, e.get_slot())),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+        }
+    }
 
-        let (_, mut dataref) = match self.id_datarefs.remove(&index) {
-            Some(e) => e,
+    /// Releases one consumer of a dataref.
+    ///
+    /// The rate is lowered when the fastest consumer leaves,
+    /// the dataref is unsubscribed from X-Plane once the last consumer is released.
+    pub async fn release<S: Transport>(&mut self, id: SubscriptionId,
+                         sending_socket: &S, receiving_address: &SocketAddr) -> io::Result<()> {
+        let name = match self.subscription_names.remove(&id) {
+            Some((_, e)) => e,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Subscription not found")),
+        };
+        let index = match self.name_id_map.get(&name) {
+            Some(e) => *e,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+        };
+
+        let message = {
+            let mut dataref = match self.id_datarefs.get_mut(&index) {
+                Some(e) => e,
+                None => return Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+            };
+
+            let previous_freq = dataref.get_freq();
+            dataref.remove_consumer(id);
+            if dataref.get_consumer_count() == 0 {
+                None
+            } else if dataref.get_freq() != previous_freq {
+                Some(dataref.subscription_message())
+            } else {
+                return Ok(());
+            }
+        };
+
+        match message {
+            Some(message) => {
+                debug!("Lowering frequency of dataref {}", name);
+                sending_socket.send_to(message.as_slice(), *receiving_address).await?;
+                Ok(())
+            }
+            None => self.remove_dataref(&name, sending_socket, receiving_address).await,
+        }
+    }
+
+    /// Unsubscribes `dataref` for all of its consumers.
+    ///
+    /// The ids of every consumer are dropped with it, their later [`DataRefHandler::release`]
+    /// fails with `NotFound`. Consumers only giving up their own subscription use `release`.
+    pub async fn unsubscribe<S: Transport>(&mut self, dataref: &str,
+                             sending_socket: &S, receiving_address: &SocketAddr) -> io::Result<()> {
+        let consumers = self.name_id_map.get(dataref)
+            .and_then(|e| self.id_datarefs.get(&*e).map(|e| e.get_consumer_count()))
+            .unwrap_or(0);
+        if consumers > 1 {
+            warn!("Unsubscribing dataref {} for all of its {} consumers, release their ids instead", dataref, consumers);
+        }
+        self.remove_dataref(dataref, sending_socket, receiving_address).await
+    }
+
+    /// Sends the freq 0 request of `dataref` and forgets it with all of its consumers
+    async fn remove_dataref<S: Transport>(&mut self, dataref: &str,
+                                          sending_socket: &S, receiving_address: &SocketAddr) -> io::Result<()> {
+        let index = match self.name_id_map.get(dataref) {
+            Some(e) => *e,
             None => return Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
         };
+        let message = match self.id_datarefs.get_mut(&index) {
+            Some(mut e) => e.unsubscribe_message(),
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+        };
+
+        // Forgotten only once X-Plane was told, so an interrupted send leaves it to `unsubscribe_all_now`
+        sending_socket.send_to(message.as_slice(), *receiving_address).await?;
+
+        self.name_id_map.remove(dataref);
+        if let Some((_, dataref)) = self.id_datarefs.remove(&index) {
+            for id in dataref.get_consumers() {
+                self.subscription_names.remove(&id);
+            }
+        }
+        self.indexes.release(index);
 
-        let message = dataref.unsubscribe_message();
-        sending_socket.send_to(message.as_slice(), receiving_address).await?;
+        Ok(())
+    }
+
+    /// Sends the current subscription of `dataref` again, restarting its confirmation window.
+    pub async fn resubscribe<S: Transport>(&mut self, dataref: &str,
+                             sending_socket: &S, receiving_address: &SocketAddr) -> io::Result<()> {
+        let index = match self.name_id_map.get(dataref) {
+            Some(e) => *e,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+        };
+        let message = match self.id_datarefs.get_mut(&index) {
+            Some(mut e) => {
+                e.mark_resubscribed();
+                e.subscription_message()
+            }
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+        };
 
+        sending_socket.send_to(message.as_slice(), *receiving_address).await?;
         Ok(())
     }
 
-    pub async fn unsubscribe_all(&mut self, sending_socket: &UdpSocket, receiving_address: &SocketAddr) -> io::Result<()> {
+    /// Subscribes a batch of datarefs, pacing the RREF requests so X-Plane does not drop them.
+    ///
+    /// Datarefs that do not start reporting within the confirmation timeout are sent again,
+    /// up to [`XP_BATCH_MAX_RETRIES`] times. Datarefs that never answer stay subscribed
+    /// with [`SubscriptionStatus::NoResponse`] and are reported as `TimedOut`.
+    pub async fn subscribe_many<S: Transport>(&mut self, datarefs: &[(&str, i32, DataRefType)],
+                                sending_socket: &S, receiving_address: &SocketAddr) -> Vec<BatchResult<SubscriptionId>> {
+        let interval = Duration::from_millis(XP_BATCH_SEND_INTERVAL_MS);
+        let mut results = Vec::with_capacity(datarefs.len());
+
+        for (i, (name, frequency, dataref_type)) in datarefs.iter().enumerate() {
+            if i > 0 {
+                B::sleep(interval).await;
+            }
+            let result = self.new_subscribe(name, *frequency, *dataref_type, sending_socket, receiving_address).await;
+            results.push(BatchResult { dataref: name.to_string(), attempts: 1, result });
+        }
+
+        let mut attempt = 0;
+        loop {
+            // Wait until every sent subscription is either confirmed or timed out
+            let deadline = Instant::now() + self.confirm_timeout;
+            let _ = self.wait_until(deadline, || {
+                let pending = results.iter()
+                    .filter(|r| r.result.is_ok())
+                    .any(|r| self.get_status(&r.dataref) == Some(SubscriptionStatus::Pending));
+                Ok((!pending).then_some(()))
+            }).await;
+
+            let stragglers: Vec<usize> = results.iter().enumerate()
+                .filter(|(_, r)| r.result.is_ok())
+                .filter(|(_, r)| self.get_status(&r.dataref) != Some(SubscriptionStatus::Active))
+                .map(|(i, _)| i)
+                .collect();
+
+            if stragglers.is_empty() {
+                break;
+            }
+            if attempt >= XP_BATCH_MAX_RETRIES {
+                for i in stragglers {
+                    error!("No response for dataref {} after {} attempts", results[i].dataref, results[i].attempts);
+                    results[i].result = Err(io::Error::new(
+                        io::ErrorKind::TimedOut, format!("No response for dataref {}", results[i].dataref)));
+                }
+                break;
+            }
+
+            attempt += 1;
+            debug!("Resending {} unanswered subscriptions, retry {}/{}", stragglers.len(), attempt, XP_BATCH_MAX_RETRIES);
+            for (n, i) in stragglers.into_iter().enumerate() {
+                if n > 0 {
+                    B::sleep(interval).await;
+                }
+                let name = results[i].dataref.clone();
+                results[i].attempts += 1;
+                if let Err(e) = self.resubscribe(&name, sending_socket, receiving_address).await {
+                    results[i].result = Err(e);
+                }
+            }
+        }
+
+        results
+    }
+
+    /// Unsubscribes a batch of datarefs for all of their consumers, pacing the RREF requests.
+    pub async fn unsubscribe_many<S: Transport>(&mut self, datarefs: &[&str],
+                                  sending_socket: &S, receiving_address: &SocketAddr) -> Vec<BatchResult<()>> {
+        let interval = Duration::from_millis(XP_BATCH_SEND_INTERVAL_MS);
+        let mut results = Vec::with_capacity(datarefs.len());
+
+        for (i, name) in datarefs.iter().enumerate() {
+            if i > 0 {
+                B::sleep(interval).await;
+            }
+            let result = self.unsubscribe(name, sending_socket, receiving_address).await;
+            results.push(BatchResult { dataref: name.to_string(), attempts: 1, result });
+        }
+
+        results
+    }
+
+    /// Releases a batch of consumers with paced sends, one result per id.
+    pub async fn release_many<S: Transport>(&mut self, ids: &[SubscriptionId],
+                              sending_socket: &S, receiving_address: &SocketAddr) -> Vec<BatchResult<()>> {
+        let interval = Duration::from_millis(XP_BATCH_SEND_INTERVAL_MS);
+        let mut results = Vec::with_capacity(ids.len());
+
+        for (i, id) in ids.iter().enumerate() {
+            if i > 0 {
+                B::sleep(interval).await;
+            }
+            let dataref = self.subscription_names.get(id).map(|e| e.value().clone()).unwrap_or_default();
+            let result = self.release(*id, sending_socket, receiving_address).await;
+            results.push(BatchResult { dataref, attempts: 1, result });
+        }
+
+        results
+    }
+
+    pub async fn unsubscribe_all<S: Transport>(&mut self, sending_socket: &S, receiving_address: &SocketAddr) -> io::Result<()> {
         let keys: Vec<String> = self.name_id_map.iter().map(|e| e.key().clone()).collect();
         for name in keys {
-            self.unsubscribe(name.as_str(), sending_socket, receiving_address).await?;
+            self.remove_dataref(name.as_str(), sending_socket, receiving_address).await?;
         }
 
+        self.id_datarefs.clear();
+        self.name_id_map.clear();
+        self.subscription_names.clear();
+
+        Ok(())
+    }
+
+    /// Unsubscribes everything without awaiting, for teardown paths such as `Drop`.
+    ///
+    /// Requests the socket cannot take right away are dropped, failures are only logged.
+    pub fn unsubscribe_all_now<S: Transport>(&mut self, sending_socket: &S, receiving_address: &SocketAddr) {
+        for mut dataref in self.id_datarefs.iter_mut() {
+            let message = dataref.unsubscribe_message();
+            if let Err(e) = sending_socket.try_send_to(message.as_slice(), *receiving_address) {
+                error!("Failed to unsubscribe dataref {}: {}", dataref.get_name(), e);
+            }
+            self.indexes.release(dataref.get_index());
+        }
 
         self.id_datarefs.clear();
         self.name_id_map.clear();
+        self.subscription_names.clear();
+    }
+
+    pub fn set_confirm_timeout(&mut self, confirm_timeout: Duration) {
+        self.confirm_timeout = confirm_timeout;
+    }
+
+    pub fn get_confirm_timeout(&self) -> Duration {
+        self.confirm_timeout
+    }
+
+    /// Validates subscriptions and writes against `catalog`, `None` turns validation off
+    pub fn set_catalog(&mut self, catalog: Option<Arc<Catalog>>) {
+        self.catalog = catalog;
+    }
+
+    pub fn get_catalog(&self) -> Option<&Arc<Catalog>> {
+        self.catalog.as_ref()
+    }
 
+    pub fn get_status(&self, dataref: &str) -> Option<SubscriptionStatus> {
+        let index = match self.name_id_map.get(dataref) {
+            Some(e) => *e,
+            None => return None,
+        };
+        self.id_datarefs.get_mut(&index).map(|mut e| e.status(self.confirm_timeout))
+    }
+
+    /// Re-evaluates `check` after every processed RREF message until it yields a value.
+    ///
+    /// Returns `Ok(None)` once `deadline` has passed without `check` being satisfied.
+    async fn wait_until<T, F>(&self, deadline: Instant, mut check: F) -> io::Result<Option<T>>
+    where
+        F: FnMut() -> io::Result<Option<T>>,
+    {
+        // One timer for the whole wait, not one per received message
+        let mut timeout = pin!(B::sleep(deadline.saturating_duration_since(Instant::now())));
+        loop {
+            // Register for the wake-up before checking, so an update in between is not lost
+            let notified = self.updated.notified();
+
+            if let Some(value) = check()? {
+                return Ok(Some(value));
+            }
+
+            if Instant::now() >= deadline {
+                return check();
+            }
+            if let Either::Right(()) = race(notified, timeout.as_mut()).await {
+                return check();
+            }
+        }
+    }
+
+    /// Waits until X-Plane answers the subscription of `dataref` or the confirmation timeout runs out.
+    ///
+    /// Returns `TimedOut` if no value for the dataref index was received in time,
+    /// which usually means the dataref does not exist.
+    pub async fn wait_confirmed(&self, dataref: &str) -> io::Result<()> {
+        let index = match self.name_id_map.get(dataref) {
+            Some(e) => *e,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+        };
+        let subscribed_at = match self.id_datarefs.get(&index) {
+            Some(e) => e.get_subscribed_at(),
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+        };
+        let deadline = subscribed_at + self.confirm_timeout;
+
+        let confirmed = self.wait_until(deadline, || {
+            match self.id_datarefs.get_mut(&index) {
+                Some(mut e) => match e.status(self.confirm_timeout) {
+                    SubscriptionStatus::Active => Ok(Some(())),
+                    SubscriptionStatus::Pending => Ok(None),
+                    SubscriptionStatus::NoResponse => Err(io::Error::new(
+                        io::ErrorKind::TimedOut, format!("No response for dataref {}", dataref))),
+                },
+                None => Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+            }
+        }).await;
+
+        match confirmed {
+            Ok(Some(())) => Ok(()),
+            Ok(None) => Err(io::Error::new(
+                io::ErrorKind::TimedOut, format!("No response for dataref {}", dataref))),
+            Err(e) => {
+                error!("No response from X-Plane for dataref {}", dataref);
+                Err(e)
+            }
+        }
+    }
+
+    /// Waits until the value of `dataref` satisfies `predicate` and returns that value.
+    ///
+    /// The predicate is evaluated on every received RREF message,
+    /// it also sees [`DataRefValueType::Unknown`] while no value has arrived yet.
+    pub async fn wait_for<F>(&self, dataref: &str, predicate: F, duration: Duration) -> io::Result<DataRefValueType>
+    where
+        F: Fn(&DataRefValueType) -> bool,
+    {
+        let deadline = Instant::now() + duration;
+        let value = self.wait_until(deadline, || {
+            match self.get_dataref(dataref) {
+                Some(value) if predicate(&value) => Ok(Some(value)),
+                Some(_) => Ok(None),
+                None => Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+            }
+        }).await?;
+
+        value.ok_or_else(|| io::Error::new(
+            io::ErrorKind::TimedOut, format!("Timed out waiting for dataref {}", dataref)))
+    }
+
+    /// Waits until the values of all `datarefs` together satisfy `predicate`.
+    ///
+    /// The predicate receives the values in the order of `datarefs`,
+    /// the same values are returned once it is satisfied.
+    pub async fn wait_for_all<F>(&self, datarefs: &[&str], predicate: F, duration: Duration) -> io::Result<Vec<DataRefValueType>>
+    where
+        F: Fn(&[DataRefValueType]) -> bool,
+    {
+        let deadline = Instant::now() + duration;
+        let values = self.wait_until(deadline, || {
+            let mut values = Vec::with_capacity(datarefs.len());
+            for dataref in datarefs {
+                match self.get_dataref(dataref) {
+                    Some(value) => values.push(value),
+                    None => return Err(io::Error::new(
+                        io::ErrorKind::NotFound, format!("Dataref {} not found", dataref))),
+                }
+            }
+            Ok(predicate(&values).then_some(values))
+        }).await?;
+
+        values.ok_or_else(|| io::Error::new(
+            io::ErrorKind::TimedOut, format!("Timed out waiting for datarefs {:?}", datarefs)))
+    }
+
+    fn set_message(dataref: &str, value: f32) -> io::Result<Vec<u8>> {
+        // Python 3 struct.pack arg: '<4sxf500s'
+        // <: little-endian
+        // 4s: 4 byte string
+        // x: pad byte
+        // f: 4 byte float
+        // 500s: 500 byte string
+        // ref: https://xppython3.readthedocs.io/en/latest/development/udp/dref.html
+        let name_len = dataref.len();
+        let max_name_len = 500;
+        if name_len >= max_name_len {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Dataref name is too long"));
+        }
+
+        let len = 4 + 1 + 4;
+        let mut message = vec![0; len + max_name_len];
+        message[0..4].copy_from_slice(DREF_PREFIX);
+        message[5..9].copy_from_slice(&value.to_le_bytes());
+        message[9..9+name_len].copy_from_slice(dataref.as_bytes());
+
+        Ok(message)
+    }
+
+    /// Writes `value` to a dataref in X-Plane, the dataref does not need to be subscribed.
+    pub async fn set_dataref<S: Transport>(&self, dataref: &str, value: f32,
+                             sending_socket: &S, receiving_address: &SocketAddr) -> io::Result<()> {
+        debug!("Setting dataref {} to {}", dataref, value);
+        if let Some(catalog) = &self.catalog {
+            match catalog.get(dataref) {
+                Some(e) if !e.is_writable() => warn!("Writing dataref {}, which the catalog lists as read-only", dataref),
+                None => warn!("Writing dataref {}, which is not in the catalog", dataref),
+                _ => {}
+            }
+        }
+        let message = Self::set_message(dataref, value)?;
+        sending_socket.send_to(message.as_slice(), *receiving_address).await?;
         Ok(())
     }
 
@@ -179,12 +687,176 @@ impl DataRefHandler {
         };
         self.id_datarefs.get(&index).map(|e| e.get())
     }
+
+    pub fn get_dataref_with_meta(&self, dataref: &str) -> Option<(DataRefValueType, DataRefMeta)> {
+        let index = match self.name_id_map.get(dataref) {
+            Some(e) => *e,
+            None => return None,
+        };
+        self.id_datarefs.get(&index).map(|e| (e.get(), e.get_meta()))
+    }
+
+    /// Values of the given datarefs as of the last fully applied RREF message.
+    ///
+    /// Datarefs that are not subscribed are left out of the snapshot.
+    pub fn snapshot(&self, datarefs: &[&str]) -> Snapshot {
+        let sequence = self.frame.read().unwrap_or_else(|e| e.into_inner());
+
+        let values = datarefs.iter()
+            .filter_map(|name| {
+                let index = *self.name_id_map.get(*name)?;
+                let value = self.id_datarefs.get(&index)?.get();
+                Some((name.to_string(), value))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        Snapshot::new(*sequence, values)
+    }
+
+    /// Values of all subscribed datarefs as of the last fully applied RREF message.
+    pub fn snapshot_all(&self) -> Snapshot {
+        let sequence = self.frame.read().unwrap_or_else(|e| e.into_inner());
+
+        let values = self.id_datarefs.iter()
+            .map(|e| (e.get_name().to_string(), e.get()))
+            .collect::<BTreeMap<_, _>>();
+
+        Snapshot::new(*sequence, values)
+    }
+
+    /// Asks for the last `depth` values of the dataref consumer `id` subscribed, 0 for none.
+    ///
+    /// The dataref keeps the highest depth of all its consumers,
+    /// capped at [`XP_HISTORY_MAX_DEPTH`](crate::consts::XP_HISTORY_MAX_DEPTH).
+    pub fn set_history_depth(&self, id: SubscriptionId, depth: usize) -> io::Result<()> {
+        let name = match self.subscription_names.get(&id) {
+            Some(e) => e.value().clone(),
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Subscription not found")),
+        };
+        let index = match self.name_id_map.get(&name) {
+            Some(e) => *e,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+        };
+        let found = match self.id_datarefs.get_mut(&index) {
+            Some(mut e) => e.set_history_depth(id, depth),
+            None => false,
+        };
+        match found {
+            true => Ok(()),
+            false => Err(io::Error::new(io::ErrorKind::NotFound, "Subscription not found")),
+        }
+    }
+
+    /// Declares the unit X-Plane sends `dataref` in, `None` removes it
+    pub fn set_unit(&self, dataref: &str, unit: Option<Unit>) -> io::Result<()> {
+        let index = match self.name_id_map.get(dataref) {
+            Some(e) => *e,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+        };
+        match self.id_datarefs.get_mut(&index) {
+            Some(mut e) => {
+                e.set_unit(unit);
+                Ok(())
+            }
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+        }
+    }
+
+    /// Value of `dataref` together with its declared unit
+    pub fn get_dataref_with_unit(&self, dataref: &str) -> Option<(DataRefValueType, Option<Unit>)> {
+        let index = match self.name_id_map.get(dataref) {
+            Some(e) => *e,
+            None => return None,
+        };
+        self.id_datarefs.get(&index).map(|e| (e.get(), e.get_unit()))
+    }
+
+    /// Copy of the history of `dataref`, from the oldest to the newest value.
+    pub fn history(&self, dataref: &str) -> Option<Vec<(Instant, DataRefValueType)>> {
+        let index = match self.name_id_map.get(dataref) {
+            Some(e) => *e,
+            None => return None,
+        };
+        self.id_datarefs.get(&index).map(|e| e.history().collect())
+    }
+
+    /// Whether the value of `dataref` is older than `max_age`.
+    ///
+    /// Datarefs that are not subscribed or have not received a value yet are always stale.
+    pub fn is_stale(&self, dataref: &str, max_age: Duration) -> bool {
+        match self.get_dataref_with_meta(dataref) {
+            Some((_, meta)) => meta.is_stale(max_age),
+            None => true,
+        }
+    }
 }
 
-impl Drop for DataRefHandler {
+impl<B: Backend> Drop for DataRefHandler<B> {
     fn drop(&mut self) {
-        if let Some(handle) = self.handle.take() {
-            handle.abort();
+        self.stop_run_thread();
+    }
+}
+#[cfg(all(test, any(feature = "tokio", feature = "std")))]
+mod tests {
+    use std::future;
+
+    use super::*;
+    use crate::backend::{DefaultBackend, Executor};
+    use crate::transport::ChannelTransport;
+
+    const DATAREF: &str = "sim/cockpit2/gauges/indicators/airspeed_kts_pilot";
+
+    /// Transport whose sends always fail
+    struct UnreachableTransport;
+
+    impl Transport for UnreachableTransport {
+        async fn send_to(&self, _buf: &[u8], _target: SocketAddr) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::ConnectionRefused, "unreachable"))
+        }
+
+        fn try_send_to(&self, _buf: &[u8], _target: SocketAddr) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::ConnectionRefused, "unreachable"))
+        }
+
+        async fn recv_from(&self, _buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+            future::pending().await
         }
+
+        fn local_addr(&self) -> io::Result<SocketAddr> {
+            Ok(([127, 0, 0, 1], 0).into())
+        }
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        Executor::block_on(&DefaultBackend::executor().unwrap(), future)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn failed_subscribe_leaves_no_dataref_behind() {
+        let mut handler = DataRefHandler::<DefaultBackend>::new();
+        let address = ([127, 0, 0, 1], 49000).into();
+
+        let result = block_on(handler.new_subscribe(DATAREF, 5, DataRefType::Float, &UnreachableTransport, &address));
+        assert!(result.is_err());
+        assert!(handler.get_dataref(DATAREF).is_none());
+        assert!(handler.subscription_names.is_empty());
+        assert!(handler.id_datarefs.is_empty());
+    }
+
+    #[test]
+    fn failed_subscribe_keeps_existing_consumers() {
+        let mut handler = DataRefHandler::<DefaultBackend>::new();
+        let address = ([127, 0, 0, 1], 49000).into();
+        let (transport, _xplane) = ChannelTransport::pair(([127, 0, 0, 1], 49001).into(), address);
+
+        let id = block_on(handler.new_subscribe(DATAREF, 1, DataRefType::Float, &transport, &address)).unwrap();
+        let result = block_on(handler.new_subscribe(DATAREF, 10, DataRefType::Float, &UnreachableTransport, &address));
+        assert!(result.is_err());
+
+        let index = *handler.name_id_map.get(DATAREF).unwrap();
+        let dataref = handler.id_datarefs.get(&index).unwrap();
+        assert_eq!(dataref.get_freq(), 1);
+        assert_eq!(dataref.get_consumers().collect::<Vec<_>>(), vec![id]);
+        assert_eq!(handler.subscription_names.len(), 1);
+    }
+}
@@ -1,13 +1,82 @@
+use crate::consts::XP_BOOL_THRESHOLD;
+use crate::error::XPlaneError;
+
+/// How the raw f32 X-Plane sends for every dataref is interpreted.
+///
+/// With the `serde` feature, unit variants serialize as their snake case name and
+/// `Scaled` as a single-key object:
+///
+/// ```json
+/// "float"
+/// {"scaled": {"factor": 0.01, "offset": 0.0}}
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum DataRefType {
     Float,
     Int,
     Char,
+    /// Switch states and flags, `true` above [`XP_BOOL_THRESHOLD`]
+    Bool,
+    /// Widened to f64, for values that are accumulated
+    Double,
+    Byte,
+    /// `raw * factor + offset` as a [`DataRefValueType::Double`], e.g. frequencies stored ×100
+    Scaled { factor: f64, offset: f64 },
+}
+
+impl DataRefType {
+    /// Scaled type without an offset
+    pub fn scaled(factor: f64) -> Self {
+        DataRefType::Scaled { factor, offset: 0.0 }
+    }
+
+    /// Interprets a raw RREF value, X-Plane sends every dataref as a float
+    pub fn convert(&self, raw: f32) -> DataRefValueType {
+        match *self {
+            DataRefType::Float => DataRefValueType::Float(raw),
+            DataRefType::Int => DataRefValueType::Int(raw_to_int(raw)),
+            DataRefType::Char => DataRefValueType::Char(raw_to_char(raw)),
+            DataRefType::Bool => DataRefValueType::Bool(raw_to_bool(raw)),
+            DataRefType::Double => DataRefValueType::Double(raw_to_double(raw)),
+            DataRefType::Byte => DataRefValueType::Byte(raw_to_byte(raw)),
+            DataRefType::Scaled { factor, offset } => DataRefValueType::Double(raw_to_double(raw) * factor + offset),
+        }
+    }
 }
 
+/// Type of profile fields and aliases that leave out `type`
+#[cfg(feature = "serde")]
+pub(crate) fn default_type() -> DataRefType { DataRefType::Float }
+
+// ─── Conversion rules of raw values, shared with the typed handles ───────────
+pub(crate) fn raw_to_int(raw: f32) -> i32 { raw as i32 }
+pub(crate) fn raw_to_char(raw: f32) -> char { raw_to_byte(raw) as char }
+pub(crate) fn raw_to_bool(raw: f32) -> bool { raw > XP_BOOL_THRESHOLD }
+pub(crate) fn raw_to_double(raw: f32) -> f64 { raw as f64 }
+pub(crate) fn raw_to_byte(raw: f32) -> u8 { raw as u8 }
+
+/// Received value of a dataref.
+///
+/// With the `serde` feature, values serialize with their type next to them,
+/// `Char` as a one-character string:
+///
+/// ```json
+/// {"type": "float", "value": 1.5}
+/// {"type": "char", "value": "A"}
+/// {"type": "unknown"}
+/// ```
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value", rename_all = "snake_case"))]
 pub enum DataRefValueType {
     Float(f32),
     Int(i32),
     Char(char),
+    Bool(bool),
+    Double(f64),
+    Byte(u8),
     Unknown,
 }
 
@@ -17,6 +86,9 @@ impl PartialEq for DataRefValueType {
             (DataRefValueType::Float(a), DataRefValueType::Float(b)) => a == b,
             (DataRefValueType::Int(a), DataRefValueType::Int(b)) => a == b,
             (DataRefValueType::Char(a), DataRefValueType::Char(b)) => a == b,
+            (DataRefValueType::Bool(a), DataRefValueType::Bool(b)) => a == b,
+            (DataRefValueType::Double(a), DataRefValueType::Double(b)) => a == b,
+            (DataRefValueType::Byte(a), DataRefValueType::Byte(b)) => a == b,
             (DataRefValueType::Unknown, DataRefValueType::Unknown) => true,
             _ => false,
         }
@@ -31,7 +103,153 @@ impl std::fmt::Debug for DataRefValueType {
             DataRefValueType::Float(v) => write!(f, "Float({})", v),
             DataRefValueType::Int(v) => write!(f, "Int({})", v),
             DataRefValueType::Char(v) => write!(f, "Char({})", v),
+            DataRefValueType::Bool(v) => write!(f, "Bool({})", v),
+            DataRefValueType::Double(v) => write!(f, "Double({})", v),
+            DataRefValueType::Byte(v) => write!(f, "Byte({})", v),
             DataRefValueType::Unknown => write!(f, "Unknown"),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Rust type a received value can be read as, e.g. through `Session::get`.
+///
+/// Implement it for own types, such as an enum of switch positions read from an `Int` dataref.
+pub trait FromDataRefValue: Sized {
+    /// `None` if the value cannot be read as this type
+    fn from_value(value: &DataRefValueType) -> Option<Self>;
+
+    /// Reads the value looked up for `dataref`, `None` meaning it is not subscribed
+    fn from_dataref(dataref: &str, value: Option<&DataRefValueType>) -> Result<Self, XPlaneError> {
+        match value {
+            None => Err(XPlaneError::NotSubscribed(dataref.to_string())),
+            Some(DataRefValueType::Unknown) => Err(XPlaneError::NoValue(dataref.to_string())),
+            Some(value) => Self::from_value(value).ok_or_else(|| XPlaneError::TypeMismatch {
+                dataref: dataref.to_string(),
+                expected: std::any::type_name::<Self>(),
+                found: *value,
+            }),
+        }
+    }
+}
+
+/// Doubles, including scaled values, are narrowed to f32
+impl FromDataRefValue for f32 {
+    fn from_value(value: &DataRefValueType) -> Option<Self> {
+        match *value {
+            DataRefValueType::Float(v) => Some(v),
+            DataRefValueType::Double(v) => Some(v as f32),
+            _ => None,
+        }
+    }
+}
+
+impl FromDataRefValue for f64 {
+    fn from_value(value: &DataRefValueType) -> Option<Self> {
+        match *value {
+            DataRefValueType::Double(v) => Some(v),
+            DataRefValueType::Float(v) => Some(v as f64),
+            _ => None,
+        }
+    }
+}
+
+impl FromDataRefValue for i32 {
+    fn from_value(value: &DataRefValueType) -> Option<Self> {
+        match *value {
+            DataRefValueType::Int(v) => Some(v),
+            DataRefValueType::Byte(v) => Some(v as i32),
+            _ => None,
+        }
+    }
+}
+
+impl FromDataRefValue for u8 {
+    fn from_value(value: &DataRefValueType) -> Option<Self> {
+        match *value {
+            DataRefValueType::Byte(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl FromDataRefValue for bool {
+    fn from_value(value: &DataRefValueType) -> Option<Self> {
+        match *value {
+            DataRefValueType::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl FromDataRefValue for char {
+    fn from_value(value: &DataRefValueType) -> Option<Self> {
+        match *value {
+            DataRefValueType::Char(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Text of any received value, for display purposes
+impl FromDataRefValue for String {
+    fn from_value(value: &DataRefValueType) -> Option<Self> {
+        match *value {
+            DataRefValueType::Float(v) => Some(v.to_string()),
+            DataRefValueType::Int(v) => Some(v.to_string()),
+            DataRefValueType::Char(v) => Some(v.to_string()),
+            DataRefValueType::Bool(v) => Some(v.to_string()),
+            DataRefValueType::Double(v) => Some(v.to_string()),
+            DataRefValueType::Byte(v) => Some(v.to_string()),
+            DataRefValueType::Unknown => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn f32_reads_doubles_and_scaled_values() {
+        assert_eq!(f32::from_value(&DataRefValueType::Float(1.5)), Some(1.5));
+        assert_eq!(f32::from_value(&DataRefValueType::Double(2.25)), Some(2.25));
+
+        let frequency = DataRefType::scaled(0.01).convert(11030.0);
+        assert_eq!(frequency, DataRefValueType::Double(110.3));
+        assert_eq!(f32::from_value(&frequency), Some(110.3));
+        assert_eq!(f32::from_value(&DataRefValueType::Int(1)), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn types_serialize_in_stable_format() {
+        let types = [
+            (DataRefType::Float, r#""float""#),
+            (DataRefType::Bool, r#""bool""#),
+            (DataRefType::Byte, r#""byte""#),
+            (DataRefType::Scaled { factor: 0.01, offset: 5.0 }, r#"{"scaled":{"factor":0.01,"offset":5.0}}"#),
+        ];
+        for (dataref_type, json) in types {
+            assert_eq!(serde_json::to_string(&dataref_type).unwrap(), json);
+            assert_eq!(serde_json::from_str::<DataRefType>(json).unwrap(), dataref_type);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn values_serialize_in_stable_format() {
+        let values = [
+            (DataRefValueType::Float(1.5), r#"{"type":"float","value":1.5}"#),
+            (DataRefValueType::Int(-3), r#"{"type":"int","value":-3}"#),
+            (DataRefValueType::Char('A'), r#"{"type":"char","value":"A"}"#),
+            (DataRefValueType::Bool(true), r#"{"type":"bool","value":true}"#),
+            (DataRefValueType::Double(110.3), r#"{"type":"double","value":110.3}"#),
+            (DataRefValueType::Byte(255), r#"{"type":"byte","value":255}"#),
+            (DataRefValueType::Unknown, r#"{"type":"unknown"}"#),
+        ];
+        for (value, json) in values {
+            assert_eq!(serde_json::to_string(&value).unwrap(), json);
+            assert_eq!(serde_json::from_str::<DataRefValueType>(json).unwrap(), value);
+        }
+    }
+}
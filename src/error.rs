@@ -0,0 +1,80 @@
+use std::fmt;
+use std::io;
+
+use crate::dataref_type::DataRefValueType;
+use crate::units::Unit;
+
+/// Failure of a typed read such as `Session::get` or `Session::get_as`.
+///
+/// Converted to an [`io::Error`] of kind `NotFound` for [`XPlaneError::NotSubscribed`],
+/// [`XPlaneError::NoValue`] and [`XPlaneError::UnknownAlias`], `InvalidData` for
+/// [`XPlaneError::TypeMismatch`] and `InvalidInput` for the unit errors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum XPlaneError {
+    /// The dataref is not subscribed
+    NotSubscribed(String),
+    /// The dataref is subscribed, but X-Plane has not sent a value yet
+    NoValue(String),
+    /// The value cannot be read as the requested type
+    TypeMismatch {
+        dataref: String,
+        expected: &'static str,
+        found: DataRefValueType,
+    },
+    /// No unit was declared for the dataref, so it cannot be converted
+    NoUnit(String),
+    /// The declared unit cannot be converted to the requested one
+    IncompatibleUnit {
+        dataref: String,
+        unit: Unit,
+        requested: Unit,
+    },
+    /// The alias resolves to no dataref, see [`crate::alias::AliasRegistry`]
+    UnknownAlias(String),
+}
+
+impl fmt::Display for XPlaneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XPlaneError::NotSubscribed(dataref) => write!(f, "Dataref {} is not subscribed", dataref),
+            XPlaneError::NoValue(dataref) => write!(f, "No value received for dataref {} yet", dataref),
+            XPlaneError::TypeMismatch { dataref, expected, found } => {
+                write!(f, "Dataref {} holds {:?}, which cannot be read as {}", dataref, found, expected)
+            }
+            XPlaneError::NoUnit(dataref) => write!(f, "No unit declared for dataref {}", dataref),
+            XPlaneError::IncompatibleUnit { dataref, unit, requested } => {
+                write!(f, "Dataref {} is in {}, which cannot be converted to {}", dataref, unit, requested)
+            }
+            XPlaneError::UnknownAlias(alias) => write!(f, "No dataref known for alias {}", alias),
+        }
+    }
+}
+
+impl std::error::Error for XPlaneError {}
+
+impl From<XPlaneError> for io::Error {
+    fn from(error: XPlaneError) -> Self {
+        let kind = match error {
+            XPlaneError::NotSubscribed(_) | XPlaneError::NoValue(_) | XPlaneError::UnknownAlias(_) => io::ErrorKind::NotFound,
+            XPlaneError::TypeMismatch { .. } => io::ErrorKind::InvalidData,
+            XPlaneError::NoUnit(_) | XPlaneError::IncompatibleUnit { .. } => io::ErrorKind::InvalidInput,
+        };
+        io::Error::new(kind, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_error_kinds() {
+        let kind = |e: XPlaneError| io::Error::from(e).kind();
+        assert_eq!(kind(XPlaneError::NotSubscribed("a".to_string())), io::ErrorKind::NotFound);
+        assert_eq!(kind(XPlaneError::NoValue("a".to_string())), io::ErrorKind::NotFound);
+        assert_eq!(kind(XPlaneError::UnknownAlias("a".to_string())), io::ErrorKind::NotFound);
+        assert_eq!(kind(XPlaneError::TypeMismatch { dataref: "a".to_string(), expected: "f32", found: DataRefValueType::Int(1) }),
+                   io::ErrorKind::InvalidData);
+        assert_eq!(kind(XPlaneError::NoUnit("a".to_string())), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,14 @@
+//! Structs mapped to a group of datarefs, usually through `#[derive(XPlaneDataRefs)]`
+//! from the `derive` feature.
+
+use crate::dataref_type::DataRefType;
+use crate::error::XPlaneError;
+use crate::snapshot::Snapshot;
+
+pub trait DataRefGroup: Sized {
+    /// Name, frequency and type of every dataref of the group
+    fn datarefs() -> Vec<(&'static str, i32, DataRefType)>;
+
+    /// Builds the group from a snapshot containing its datarefs
+    fn from_snapshot(snapshot: &Snapshot) -> Result<Self, XPlaneError>;
+}
@@ -0,0 +1,121 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Hands out RREF indexes and recycles the released ones.
+///
+/// A released index is quarantined for a while before it can be handed out again,
+/// X-Plane may still have packets for the old subscription in flight,
+/// and those must not be credited to the next dataref using the same index.
+pub(crate) struct IndexAllocator {
+    next: i32,
+    free: BTreeSet<i32>,
+    quarantine: VecDeque<(i32, Instant)>,
+    quarantine_duration: Duration,
+}
+
+impl IndexAllocator {
+    pub(crate) fn new(quarantine_duration: Duration) -> Self {
+        IndexAllocator {
+            next: 1,
+            free: BTreeSet::new(),
+            quarantine: VecDeque::new(),
+            quarantine_duration,
+        }
+    }
+
+    pub(crate) fn allocate(&mut self) -> i32 {
+        self.release_quarantined();
+
+        // Prefer the lowest free index to keep the index space compact
+        if let Some(index) = self.free.pop_first() {
+            return index;
+        }
+
+        let index = self.next;
+        self.next += 1;
+        index
+    }
+
+    pub(crate) fn release(&mut self, index: i32) {
+        self.quarantine.push_back((index, Instant::now()));
+    }
+
+    fn release_quarantined(&mut self) {
+        // Entries are pushed in release order, so the expired ones are always at the front
+        while let Some(&(index, released_at)) = self.quarantine.front() {
+            if released_at.elapsed() < self.quarantine_duration {
+                break;
+            }
+            self.quarantine.pop_front();
+            self.free.insert(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    const QUARANTINE: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn allocates_consecutive_indexes() {
+        let mut allocator = IndexAllocator::new(QUARANTINE);
+        assert_eq!(allocator.allocate(), 1);
+        assert_eq!(allocator.allocate(), 2);
+        assert_eq!(allocator.allocate(), 3);
+    }
+
+    #[test]
+    fn released_index_is_not_reused_during_quarantine() {
+        let mut allocator = IndexAllocator::new(QUARANTINE);
+        let first = allocator.allocate();
+        allocator.allocate();
+        allocator.release(first);
+
+        assert_eq!(allocator.allocate(), 3);
+        assert_eq!(allocator.allocate(), 4);
+    }
+
+    #[test]
+    fn released_index_is_reused_after_quarantine() {
+        let mut allocator = IndexAllocator::new(QUARANTINE);
+        let first = allocator.allocate();
+        allocator.allocate();
+        allocator.release(first);
+
+        thread::sleep(QUARANTINE);
+        assert_eq!(allocator.allocate(), first);
+        assert_eq!(allocator.allocate(), 3);
+    }
+
+    #[test]
+    fn lowest_expired_index_is_reused_first() {
+        let mut allocator = IndexAllocator::new(QUARANTINE);
+        for _ in 0..3 {
+            allocator.allocate();
+        }
+        allocator.release(3);
+        allocator.release(1);
+
+        thread::sleep(QUARANTINE);
+        assert_eq!(allocator.allocate(), 1);
+        assert_eq!(allocator.allocate(), 3);
+        assert_eq!(allocator.allocate(), 4);
+    }
+
+    #[test]
+    fn only_expired_indexes_leave_quarantine() {
+        let mut allocator = IndexAllocator::new(QUARANTINE);
+        allocator.allocate();
+        allocator.allocate();
+        allocator.release(1);
+        thread::sleep(QUARANTINE);
+        allocator.release(2);
+
+        assert_eq!(allocator.allocate(), 1);
+        assert_eq!(allocator.allocate(), 3);
+    }
+}
@@ -1,10 +1,37 @@
 pub mod dataref;
+#[cfg(any(feature = "tokio", feature = "std"))]
 pub mod beacon;
 pub mod beacon_data;
 pub mod consts;
 mod utils;
+mod index_allocator;
 pub mod dataref_type;
 pub mod dataref_handler;
 pub mod command_handler;
+#[cfg(any(feature = "tokio", feature = "std"))]
 pub mod session;
+#[cfg(any(feature = "tokio", feature = "std"))]
 pub mod auto_discover;
+pub mod snapshot;
+pub mod dataref_handle;
+#[cfg(any(feature = "tokio", feature = "std"))]
+pub mod blocking;
+pub mod backend;
+pub mod transport;
+pub mod client;
+pub mod error;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod group;
+pub mod units;
+pub mod catalog;
+mod catalog_file;
+pub mod command_catalog;
+pub mod alias;
+#[cfg(feature = "profiles")]
+pub mod profile;
+#[cfg(all(feature = "profiles", any(feature = "tokio", feature = "std")))]
+pub mod aircraft;
+
+#[cfg(feature = "derive")]
+pub use xplane_udp_derive::XPlaneDataRefs;
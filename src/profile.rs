@@ -0,0 +1,287 @@
+//! Aircraft profiles describing the datarefs and commands of an aircraft in a TOML or JSON file,
+//! so supporting a new aircraft does not require recompiling.
+//!
+//! A profile has named fields, which are subscribed with
+//! [`Session::subscribe_profile`](crate::session::Session::subscribe_profile),
+//! and named actions, triggered with [`Session::run_action`](crate::session::Session::run_action):
+//!
+//! ```toml
+//! name = "Zibo 737-800"
+//! aircraft = ["B737-800X"]
+//!
+//! [[fields]]
+//! name = "CRS"
+//! dataref = "laminar/B738/autopilot/course_pilot"
+//! type = "int"
+//! frequency = 5
+//! display = { style = "value", width = 10, group = "MCP" }
+//!
+//! [[fields]]
+//! name = "NAV1"
+//! dataref = "sim/cockpit2/radios/actuators/nav1_frequency_hz"
+//! scaling = { factor = 0.01 }
+//! display = { decimals = 2 }
+//!
+//! [[actions]]
+//! name = "CMD A"
+//! command = "laminar/B738/autopilot/cmd_a_press"
+//!
+//! [[actions]]
+//! name = "Gear down"
+//! dataref = "sim/cockpit/switches/gear_handle_status"
+//! value = 1.0
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::alias::AliasTarget;
+use crate::consts::XP_PROFILE_DEFAULT_FREQUENCY;
+use crate::dataref_type::{DataRefType, DataRefValueType};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AircraftProfile {
+    name: String,
+    /// ICAO codes or parts of the `.acf` path the profile applies to, see [`crate::aircraft`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aircraft: Vec<String>,
+    #[serde(default)]
+    fields: Vec<ProfileField>,
+    #[serde(default)]
+    actions: Vec<ProfileAction>,
+    /// Aircraft mappings of logical names, see [`crate::alias`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    aliases: BTreeMap<String, AliasTarget>,
+}
+
+/// Named dataref of a profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileField {
+    name: String,
+    dataref: String,
+    #[serde(rename = "type", default = "crate::dataref_type::default_type")]
+    dataref_type: DataRefType,
+    #[serde(default = "default_frequency")]
+    frequency: i32,
+    /// Overrides `type` with [`DataRefType::Scaled`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scaling: Option<Scaling>,
+    #[serde(default)]
+    display: DisplayFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Scaling {
+    factor: f64,
+    #[serde(default)]
+    offset: f64,
+}
+
+/// How an application should show a field, the library only uses it in [`ProfileField::format_value`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayFormat {
+    #[serde(default)]
+    style: DisplayStyle,
+    /// Width in characters reserved for the field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    width: Option<u16>,
+    /// Digits after the decimal point
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    decimals: Option<usize>,
+    /// Panel or row the field is shown in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayStyle {
+    /// The value itself, e.g. a selected course
+    #[default]
+    Value,
+    /// An on/off light such as an engaged mode
+    Indicator,
+}
+
+/// Named command or dataref write of a profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileAction {
+    name: String,
+    #[serde(flatten)]
+    target: ActionTarget,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ActionTarget {
+    Command { command: String },
+    Write { dataref: String, value: f32 },
+}
+
+fn default_frequency() -> i32 { XP_PROFILE_DEFAULT_FREQUENCY }
+
+fn invalid_data<E: std::error::Error + Send + Sync + 'static>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+impl AircraftProfile {
+    pub fn from_toml(contents: &str) -> io::Result<AircraftProfile> {
+        toml::from_str(contents).map_err(invalid_data)
+    }
+
+    pub fn from_json(contents: &str) -> io::Result<AircraftProfile> {
+        serde_json::from_str(contents).map_err(invalid_data)
+    }
+
+    /// Reads a profile, the format follows from the `.toml` or `.json` extension
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<AircraftProfile> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => AircraftProfile::from_toml(&contents),
+            Some("json") => AircraftProfile::from_json(&contents),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                    format!("Unknown profile format of {}, expected .toml or .json", path.display()))),
+        }
+    }
+
+    pub fn to_toml(&self) -> io::Result<String> {
+        toml::to_string_pretty(self).map_err(invalid_data)
+    }
+
+    pub fn to_json(&self) -> io::Result<String> {
+        serde_json::to_string_pretty(self).map_err(invalid_data)
+    }
+
+    pub fn get_name(&self) -> &str { &self.name }
+    pub fn get_aircraft(&self) -> &[String] { &self.aircraft }
+    pub fn get_fields(&self) -> &[ProfileField] { &self.fields }
+    pub fn get_actions(&self) -> &[ProfileAction] { &self.actions }
+    pub fn get_aliases(&self) -> &BTreeMap<String, AliasTarget> { &self.aliases }
+
+    pub fn get_field(&self, name: &str) -> Option<&ProfileField> {
+        self.fields.iter().find(|e| e.name == name)
+    }
+
+    pub fn get_action(&self, name: &str) -> Option<&ProfileAction> {
+        self.actions.iter().find(|e| e.name == name)
+    }
+
+    /// `(dataref, frequency, type)` of every field, as taken by `Session::subscribe_many`
+    pub fn subscriptions(&self) -> Vec<(&str, i32, DataRefType)> {
+        self.fields.iter()
+            .map(|e| (e.dataref.as_str(), e.frequency, e.get_dataref_type()))
+            .collect()
+    }
+}
+
+impl ProfileField {
+    pub fn get_name(&self) -> &str { &self.name }
+    pub fn get_dataref(&self) -> &str { &self.dataref }
+    pub fn get_frequency(&self) -> i32 { self.frequency }
+    pub fn get_display(&self) -> &DisplayFormat { &self.display }
+
+    /// Type the field is subscribed with, taking the scaling into account
+    pub fn get_dataref_type(&self) -> DataRefType {
+        match self.scaling {
+            Some(Scaling { factor, offset }) => DataRefType::Scaled { factor, offset },
+            None => self.dataref_type,
+        }
+    }
+
+    /// Formats a value of the field with the decimals of its display format
+    pub fn format_value(&self, value: &DataRefValueType) -> String {
+        let number = match *value {
+            DataRefValueType::Float(v) => v as f64,
+            DataRefValueType::Double(v) => v,
+            DataRefValueType::Int(v) => return v.to_string(),
+            DataRefValueType::Char(v) => return v.to_string(),
+            DataRefValueType::Bool(v) => return v.to_string(),
+            DataRefValueType::Byte(v) => return v.to_string(),
+            DataRefValueType::Unknown => return String::new(),
+        };
+        match self.display.decimals {
+            Some(decimals) => format!("{:.*}", decimals, number),
+            None => format!("{}", number),
+        }
+    }
+}
+
+impl DisplayFormat {
+    pub fn get_style(&self) -> DisplayStyle { self.style }
+    pub fn get_width(&self) -> Option<u16> { self.width }
+    pub fn get_decimals(&self) -> Option<usize> { self.decimals }
+    pub fn get_group(&self) -> Option<&str> { self.group.as_deref() }
+}
+
+impl ProfileAction {
+    pub fn get_name(&self) -> &str { &self.name }
+    pub fn get_target(&self) -> &ActionTarget { &self.target }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimal_toml_profile_uses_defaults() {
+        let profile = AircraftProfile::from_toml(r#"
+            name = "Cessna 172"
+
+            [[fields]]
+            name = "HDG"
+            dataref = "sim/cockpit2/autopilot/heading_dial_deg_mag_pilot"
+
+            [[actions]]
+            name = "AP"
+            command = "sim/autopilot/servos_toggle"
+        "#).unwrap();
+
+        assert_eq!(profile.get_name(), "Cessna 172");
+        assert!(profile.get_aircraft().is_empty());
+        let field = profile.get_field("HDG").unwrap();
+        assert_eq!(field.get_dataref_type(), DataRefType::Float);
+        assert_eq!(field.get_frequency(), XP_PROFILE_DEFAULT_FREQUENCY);
+        assert_eq!(field.get_display().get_style(), DisplayStyle::Value);
+        assert_eq!(profile.get_action("AP").unwrap().get_target(),
+                   &ActionTarget::Command { command: "sim/autopilot/servos_toggle".to_string() });
+    }
+
+    #[test]
+    fn minimal_json_profile() {
+        let profile = AircraftProfile::from_json(r#"{
+            "name": "Cessna 172",
+            "fields": [{ "name": "AP", "dataref": "sim/cockpit/autopilot/autopilot_mode", "type": "bool",
+                         "display": { "style": "indicator" } }],
+            "actions": [{ "name": "Gear down", "dataref": "sim/cockpit/switches/gear_handle_status", "value": 1.0 }]
+        }"#).unwrap();
+
+        let field = profile.get_field("AP").unwrap();
+        assert_eq!(field.get_dataref_type(), DataRefType::Bool);
+        assert_eq!(field.get_display().get_style(), DisplayStyle::Indicator);
+        assert_eq!(profile.get_action("Gear down").unwrap().get_target(),
+                   &ActionTarget::Write { dataref: "sim/cockpit/switches/gear_handle_status".to_string(), value: 1.0 });
+    }
+
+    #[test]
+    fn unknown_field_type_is_invalid_data() {
+        let error = AircraftProfile::from_toml(r#"
+            name = "Cessna 172"
+
+            [[fields]]
+            name = "HDG"
+            dataref = "sim/cockpit2/autopilot/heading_dial_deg_mag_pilot"
+            type = "quaternion"
+        "#).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let error = AircraftProfile::from_json(
+            r#"{ "name": "Cessna 172", "fields": [{ "name": "HDG", "dataref": "sim/x", "type": "quaternion" }] }"#
+        ).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,40 +1,66 @@
+use std::future::Future;
 use std::io;
 use std::io::Error;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use log::{debug, error, info};
-use tokio::net::UdpSocket;
-use tokio::runtime::Builder;
-use crate::consts::XP_DEFAULT_SENDING_PORT;
+use crate::backend::{Backend, DefaultBackend};
+use crate::consts::{XP_DEFAULT_SENDING_PORT, XP_SHUTDOWN_TIMEOUT_MS};
 use crate::beacon::Beacon;
+use crate::catalog::Catalog;
+use crate::command_catalog::CommandCatalog;
 use crate::auto_discover::AutoDiscover;
+use crate::client::XPlaneClient;
 use crate::command_handler::{AlertMessage, CommandHandler};
-use crate::dataref_type::{DataRefType, DataRefValueType};
-use crate::dataref_handler::DataRefHandler;
-
-pub struct Session {
-    beacon: Option<Beacon>,
+use crate::dataref_handle::{DataRefHandle, DataRefScalar};
+use crate::dataref::{DataRefMeta, SubscriptionId, SubscriptionStatus};
+use crate::dataref_type::{DataRefType, DataRefValueType, FromDataRefValue};
+use crate::error::XPlaneError;
+use crate::group::DataRefGroup;
+#[cfg(feature = "profiles")]
+use crate::profile::{ActionTarget, AircraftProfile};
+use crate::dataref_handler::{BatchResult, DataRefHandler};
+use crate::snapshot::Snapshot;
+use crate::transport::Transport;
+use crate::units::{Unit, UnitOf};
+use crate::utils::{race, Either};
+
+/// Connection to X-Plane, sending and receiving through the transport `T`,
+/// by default the UDP sockets of the backend `B`
+pub struct Session<B: Backend = DefaultBackend, T: Transport = <B as Backend>::Socket> {
+    beacon: Option<Beacon<B>>,
     xp_receiving_address: SocketAddr,
-    xp_receiving_socket: Arc<UdpSocket>,
+    xp_receiving_socket: Arc<T>,
 
     xp_sending_address: SocketAddr,
-    xp_sending_socket: Arc<UdpSocket>,
+    xp_sending_socket: Arc<T>,
 
-    dataref_handler: DataRefHandler,
+    dataref_handler: DataRefHandler<B>,
     command_handler: CommandHandler,
+
+    /// Time `shutdown` waits for the unsubscribe requests before falling back to non-blocking sends
+    shutdown_timeout: Duration,
 }
 
 impl Session {
     pub async fn manual(xp_receiving_address: SocketAddr,
                         xp_sending_address: SocketAddr) -> io::Result<Self> {
-        let xp_receiving_socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)).await
+        Self::manual_with_backend(xp_receiving_address, xp_sending_address).await
+    }
+}
+
+impl<B: Backend> Session<B> {
+    pub async fn manual_with_backend(xp_receiving_address: SocketAddr,
+                                     xp_sending_address: SocketAddr) -> io::Result<Self> {
+        let xp_receiving_socket = B::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0).into()).await
             .map_err(|e| {
                 error!("Failed to bind to receiving socket: {}", e);
                 e
             })?;
         debug!("Receiving socket bound to {}", xp_receiving_socket.local_addr()?);
 
-        let xp_sending_socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)).await
+        let xp_sending_socket = B::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0).into()).await
             .map_err(|e| {
                 error!("Failed to bind to sending socket: {}", e);
                 e
@@ -49,10 +75,11 @@ impl Session {
             xp_sending_socket: Arc::new(xp_sending_socket),
             dataref_handler: DataRefHandler::default(),
             command_handler: CommandHandler::default(),
+            shutdown_timeout: Duration::from_millis(XP_SHUTDOWN_TIMEOUT_MS),
         })
     }
 
-    pub async fn intercept_beacon(mut auto_discover: AutoDiscover) -> Result<Session, (Error, AutoDiscover)> {
+    pub async fn intercept_beacon(mut auto_discover: AutoDiscover<B>) -> Result<Self, (Error, AutoDiscover<B>)> {
         let beacon = auto_discover.get_beacon_mut();
 
         // Intercept beacon
@@ -91,7 +118,7 @@ impl Session {
                         This should work if network settings were not overridden in the simulator.", sending);
 
 
-        let xp_receiving_socket = match UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)).await {
+        let xp_receiving_socket = match B::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0).into()).await {
             Ok(socket) => socket,
             Err(e) => {
                 error!("Failed to bind to receiving socket: {}", e);
@@ -99,7 +126,7 @@ impl Session {
             }
         };
 
-        let xp_sending_socket = match UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)).await {
+        let xp_sending_socket = match B::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0).into()).await {
             Ok(socket) => socket,
             Err(e) => {
                 error!("Failed to bind to sending socket: {}", e);
@@ -118,9 +145,37 @@ impl Session {
             xp_sending_socket: Arc::new(xp_sending_socket),
             dataref_handler: DataRefHandler::default(),
             command_handler: CommandHandler::default(),
+            shutdown_timeout: Duration::from_millis(XP_SHUTDOWN_TIMEOUT_MS),
         })
     }
 
+}
+
+impl<T: Transport> Session<DefaultBackend, T> {
+    /// Session exchanging datagrams over `transport` instead of sockets bound by the backend.
+    ///
+    /// The transport is used for both directions, the addresses are the targets of the sent datagrams.
+    pub fn with_transport(transport: T, xp_receiving_address: SocketAddr, xp_sending_address: SocketAddr) -> Self {
+        Self::with_transport_with_backend(transport, xp_receiving_address, xp_sending_address)
+    }
+}
+
+impl<B: Backend, T: Transport> Session<B, T> {
+    pub fn with_transport_with_backend(transport: T, xp_receiving_address: SocketAddr,
+                                       xp_sending_address: SocketAddr) -> Self {
+        let transport = Arc::new(transport);
+        Session {
+            beacon: None,
+            xp_receiving_address,
+            xp_receiving_socket: transport.clone(),
+            xp_sending_address,
+            xp_sending_socket: transport,
+            dataref_handler: DataRefHandler::default(),
+            command_handler: CommandHandler::default(),
+            shutdown_timeout: Duration::from_millis(XP_SHUTDOWN_TIMEOUT_MS),
+        }
+    }
+
     async fn connect_xp(&mut self, receiving: SocketAddr, sending: SocketAddr) -> io::Result<()> {
         info!("Connecting to receiving side of X-Plane at {}", receiving);
         self.xp_receiving_address = receiving;
@@ -133,7 +188,7 @@ impl Session {
         Ok(())
     }
 
-    pub fn get_beacon(&self) -> &Option<Beacon> {
+    pub fn get_beacon(&self) -> &Option<Beacon<B>> {
         &self.beacon
     }
 
@@ -146,34 +201,163 @@ impl Session {
         Ok(())
     }
 
-    pub async fn subscribe(&mut self, dataref: &str, frequency: i32, dataref_type: DataRefType) -> io::Result<()> {
+    /// Subscribes to a dataref, returning an id to [`Session::release`] this consumer later.
+    ///
+    /// Subscribing the same dataref again shares its RREF index,
+    /// X-Plane then sends it at the highest requested frequency.
+    pub async fn subscribe(&mut self, dataref: &str, frequency: i32, dataref_type: DataRefType) -> io::Result<SubscriptionId> {
         self.dataref_handler.new_subscribe(
-            dataref, frequency, dataref_type, &self.xp_sending_socket, &self.xp_receiving_address)
+            dataref, frequency, dataref_type, self.xp_sending_socket.as_ref(), &self.xp_receiving_address)
             .await
+    }
+
+    /// Subscribes to a dataref with the type listed in the catalog,
+    /// fails with `NotFound` without a catalog or if the catalog lists no usable type
+    pub async fn subscribe_inferred(&mut self, dataref: &str, frequency: i32) -> io::Result<SubscriptionId> {
+        let dataref_type = match self.dataref_handler.get_catalog() {
+            Some(catalog) => catalog.infer_type(dataref),
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "No dataref catalog set")),
+        };
+        match dataref_type {
+            Some(dataref_type) => self.subscribe(dataref, frequency, dataref_type).await,
+            None => Err(io::Error::new(io::ErrorKind::NotFound, format!("No type known for dataref {}", dataref))),
+        }
+    }
 
+    /// Subscribes to a dataref as `T` and returns a handle for lock-free reads of its value.
+    ///
+    /// The handle is a consumer like any other, release it with [`DataRefHandle::id`].
+    pub async fn subscribe_handle<V: DataRefScalar>(&mut self, dataref: &str, frequency: i32) -> io::Result<DataRefHandle<V>> {
+        self.dataref_handler.new_subscribe_handle(
+            dataref, frequency, self.xp_sending_socket.as_ref(), &self.xp_receiving_address)
+            .await
     }
 
+    /// Releases a consumer returned by [`Session::subscribe`],
+    /// the dataref is unsubscribed once no consumers are left.
+    pub async fn release(&mut self, id: SubscriptionId) -> io::Result<()> {
+        self.dataref_handler.release(
+            id, self.xp_sending_socket.as_ref(), &self.xp_receiving_address)
+            .await
+    }
+
+    /// Releases a batch of consumers with paced sends, one result per id.
+    pub async fn release_many(&mut self, ids: &[SubscriptionId]) -> Vec<BatchResult<()>> {
+        self.dataref_handler.release_many(
+            ids, self.xp_sending_socket.as_ref(), &self.xp_receiving_address)
+            .await
+    }
+
+    /// Subscribes to a dataref and waits until X-Plane starts sending its value.
+    ///
+    /// Fails with `TimedOut` if no value arrives within the confirmation timeout,
+    /// which usually means the dataref name does not exist.
+    /// The subscription itself is kept and its status stays [`SubscriptionStatus::NoResponse`].
+    pub async fn subscribe_confirmed(&mut self, dataref: &str, frequency: i32, dataref_type: DataRefType) -> io::Result<SubscriptionId> {
+        let id = self.subscribe(dataref, frequency, dataref_type).await?;
+        self.dataref_handler.wait_confirmed(dataref).await?;
+        Ok(id)
+    }
+
+    pub fn subscription_status(&self, dataref: &str) -> Option<SubscriptionStatus> {
+        self.dataref_handler.get_status(dataref)
+    }
+
+    pub fn set_confirm_timeout(&mut self, confirm_timeout: Duration) {
+        self.dataref_handler.set_confirm_timeout(confirm_timeout);
+    }
+
+    /// Checks datarefs against `catalog`: subscriptions of unknown datarefs fail with `NotFound`,
+    /// units listed in the catalog are declared and writes to read-only datarefs are logged as warnings.
+    ///
+    /// `None` turns the validation off again.
+    pub fn set_catalog(&mut self, catalog: Option<Arc<Catalog>>) {
+        self.dataref_handler.set_catalog(catalog);
+    }
+
+    pub fn get_catalog(&self) -> Option<&Catalog> {
+        self.dataref_handler.get_catalog().map(|e| e.as_ref())
+    }
+
+    /// Checks commands against `catalog`, [`Session::cmd`] then fails with `NotFound`
+    /// for unknown commands and suggests the closest known ones.
+    ///
+    /// `None` turns the validation off again.
+    pub fn set_command_catalog(&mut self, catalog: Option<Arc<CommandCatalog>>) {
+        self.command_handler.set_catalog(catalog);
+    }
+
+    pub fn get_command_catalog(&self) -> Option<&CommandCatalog> {
+        self.command_handler.get_catalog().map(|e| e.as_ref())
+    }
+
+    /// Waits until `predicate` holds for the value of `dataref`, or fails with `TimedOut` after `timeout`.
+    ///
+    /// Checked whenever an RREF message arrives, there is no polling involved.
+    pub async fn wait_for<F>(&self, dataref: &str, predicate: F, timeout: Duration) -> io::Result<DataRefValueType>
+    where
+        F: Fn(&DataRefValueType) -> bool,
+    {
+        self.dataref_handler.wait_for(dataref, predicate, timeout).await
+    }
+
+    /// Multi-dataref variant of [`Session::wait_for`], `predicate` receives the values in the order of `datarefs`.
+    pub async fn wait_for_all<F>(&self, datarefs: &[&str], predicate: F, timeout: Duration) -> io::Result<Vec<DataRefValueType>>
+    where
+        F: Fn(&[DataRefValueType]) -> bool,
+    {
+        self.dataref_handler.wait_for_all(datarefs, predicate, timeout).await
+    }
+
+    /// Subscribes to a batch of `(dataref, frequency, type)` with paced sends,
+    /// resending the subscriptions X-Plane did not answer.
+    ///
+    /// Returns one result per dataref, in the order they were given.
+    pub async fn subscribe_many(&mut self, datarefs: &[(&str, i32, DataRefType)]) -> Vec<BatchResult<SubscriptionId>> {
+        self.dataref_handler.subscribe_many(
+            datarefs, self.xp_sending_socket.as_ref(), &self.xp_receiving_address)
+            .await
+    }
+
+    /// Unsubscribes a batch of datarefs with paced sends, one result per dataref.
+    pub async fn unsubscribe_many(&mut self, datarefs: &[&str]) -> Vec<BatchResult<()>> {
+        self.dataref_handler.unsubscribe_many(
+            datarefs, self.xp_sending_socket.as_ref(), &self.xp_receiving_address)
+            .await
+    }
+
+    /// Unsubscribes a dataref for all of its consumers.
+    ///
+    /// Their ids are dropped with it and a later [`Session::release`] of them fails with `NotFound`,
+    /// a consumer giving up only its own subscription uses `release` instead.
     pub async fn unsubscribe(&mut self, dataref: &str) -> io::Result<()> {
         self.dataref_handler.unsubscribe(
-            dataref, &self.xp_sending_socket, &self.xp_receiving_address)
+            dataref, self.xp_sending_socket.as_ref(), &self.xp_receiving_address)
             .await
     }
 
     pub async fn unsubscribe_all(&mut self) -> io::Result<()> {
         self.dataref_handler.unsubscribe_all(
-            &self.xp_sending_socket, &self.xp_receiving_address)
+            self.xp_sending_socket.as_ref(), &self.xp_receiving_address)
             .await
     }
 
     pub async fn cmd(&self, command: &str) -> io::Result<()> {
         self.command_handler.send_command(
-            command, &self.xp_sending_socket, &self.xp_receiving_address)
+            command, self.xp_sending_socket.as_ref(), &self.xp_receiving_address)
+            .await
+    }
+
+    /// Writes a value to a dataref (DREF)
+    pub async fn set_dataref(&self, dataref: &str, value: f32) -> io::Result<()> {
+        self.dataref_handler.set_dataref(
+            dataref, value, self.xp_sending_socket.as_ref(), &self.xp_receiving_address)
             .await
     }
 
     pub async fn alert(&self, message: AlertMessage) -> io::Result<()> {
         self.command_handler.alert(
-            message, &self.xp_sending_socket, &self.xp_receiving_address)
+            message, self.xp_sending_socket.as_ref(), &self.xp_receiving_address)
             .await
     }
 
@@ -181,37 +365,200 @@ impl Session {
         self.dataref_handler.get_dataref(dataref)
     }
 
+    /// Last value of a subscribed dataref read as `V`, e.g. `session.get::<bool>(...)`
+    pub fn get<V: FromDataRefValue>(&self, dataref: &str) -> Result<V, XPlaneError> {
+        XPlaneClient::get(self, dataref)
+    }
+
+    /// Value of a dataref together with its receive statistics
+    pub fn get_dataref_with_meta(&self, dataref: &str) -> Option<(DataRefValueType, DataRefMeta)> {
+        self.dataref_handler.get_dataref_with_meta(dataref)
+    }
+
+    /// Frame-consistent values of the given datarefs, unsubscribed ones are left out
+    pub fn snapshot(&self, datarefs: &[&str]) -> Snapshot {
+        self.dataref_handler.snapshot(datarefs)
+    }
+
+    /// Subscribes every dataref of the group `G`
+    pub async fn subscribe_group<G: DataRefGroup>(&mut self) -> Vec<BatchResult<SubscriptionId>> {
+        let datarefs = G::datarefs();
+        self.subscribe_many(&datarefs).await
+    }
+
+    /// Reads the group `G` from a single frame-consistent snapshot
+    pub fn read_group<G: DataRefGroup>(&self) -> Result<G, XPlaneError> {
+        let names: Vec<&str> = G::datarefs().into_iter().map(|(name, _, _)| name).collect();
+        G::from_snapshot(&self.snapshot(&names))
+    }
+
+    /// Subscribes every field of an aircraft profile
+    #[cfg(feature = "profiles")]
+    pub async fn subscribe_profile(&mut self, profile: &AircraftProfile) -> Vec<BatchResult<SubscriptionId>> {
+        let datarefs = profile.subscriptions();
+        self.subscribe_many(&datarefs).await
+    }
+
+    /// Releases the subscriptions [`Session::subscribe_profile`] returned,
+    /// fields other consumers subscribed to stay subscribed.
+    #[cfg(feature = "profiles")]
+    pub async fn unsubscribe_profile(&mut self, subscriptions: &[SubscriptionId]) -> Vec<BatchResult<()>> {
+        self.release_many(subscriptions).await
+    }
+
+    /// Sends the command or dataref write of the profile action `name`
+    #[cfg(feature = "profiles")]
+    pub async fn run_action(&self, profile: &AircraftProfile, name: &str) -> io::Result<()> {
+        let action = match profile.get_action(name) {
+            Some(e) => e,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound,
+                                              format!("No action {} in profile {}", name, profile.get_name()))),
+        };
+        match action.get_target() {
+            ActionTarget::Command { command } => self.cmd(command).await,
+            ActionTarget::Write { dataref, value } => self.set_dataref(dataref, *value).await,
+        }
+    }
+
+    /// Frame-consistent values of all subscribed datarefs
+    pub fn snapshot_all(&self) -> Snapshot {
+        self.dataref_handler.snapshot_all()
+    }
+
+    /// Asks for the last `depth` received values of the dataref behind `id`, 0 for none.
+    ///
+    /// Consumers of the same dataref share its history, which keeps the highest depth any of them asked for.
+    pub fn set_history_depth(&self, id: SubscriptionId, depth: usize) -> io::Result<()> {
+        self.dataref_handler.set_history_depth(id, depth)
+    }
+
+    /// Subscribes to a dataref and keeps the last `depth` received values
+    pub async fn subscribe_with_history(&mut self, dataref: &str, frequency: i32, dataref_type: DataRefType,
+                                        depth: usize) -> io::Result<SubscriptionId> {
+        let id = self.subscribe(dataref, frequency, dataref_type).await?;
+        self.dataref_handler.set_history_depth(id, depth)?;
+        Ok(id)
+    }
+
+    /// Subscribes to a dataref X-Plane sends in `unit`, for reads with [`Session::get_as`]
+    pub async fn subscribe_with_unit(&mut self, dataref: &str, frequency: i32, dataref_type: DataRefType,
+                                     unit: Unit) -> io::Result<SubscriptionId> {
+        let id = self.subscribe(dataref, frequency, dataref_type).await?;
+        self.dataref_handler.set_unit(dataref, Some(unit))?;
+        Ok(id)
+    }
+
+    /// Declares the unit of a subscribed dataref, `None` removes it
+    pub fn set_unit(&self, dataref: &str, unit: Option<Unit>) -> io::Result<()> {
+        self.dataref_handler.set_unit(dataref, unit)
+    }
+
+    pub fn get_unit(&self, dataref: &str) -> Option<Unit> {
+        self.dataref_handler.get_dataref_with_unit(dataref).and_then(|(_, unit)| unit)
+    }
+
+    /// Last value of a subscribed dataref converted from its declared unit to `U`,
+    /// e.g. `session.get_as::<Feet>("sim/flightmodel/position/elevation")`
+    pub fn get_as<U: UnitOf>(&self, dataref: &str) -> Result<f64, XPlaneError> {
+        let (value, unit) = match self.dataref_handler.get_dataref_with_unit(dataref) {
+            Some((value, unit)) => (value, unit),
+            None => return Err(XPlaneError::NotSubscribed(dataref.to_string())),
+        };
+        // Whole numbers carry units too, e.g. an altitude selector in feet subscribed as Int
+        let value = match value {
+            DataRefValueType::Int(v) => v as f64,
+            DataRefValueType::Byte(v) => v as f64,
+            _ => f64::from_dataref(dataref, Some(&value))?,
+        };
+        let unit = unit.ok_or_else(|| XPlaneError::NoUnit(dataref.to_string()))?;
+        unit.convert(value, U::UNIT).ok_or_else(|| XPlaneError::IncompatibleUnit {
+            dataref: dataref.to_string(),
+            unit,
+            requested: U::UNIT,
+        })
+    }
+
+    /// Received values of a dataref with their arrival instants, oldest first
+    pub fn history(&self, dataref: &str) -> Option<Vec<(Instant, DataRefValueType)>> {
+        self.dataref_handler.history(dataref)
+    }
+
+    /// Whether the value of `dataref` is older than `max_age`, unsubscribed datarefs are always stale
+    pub fn is_stale(&self, dataref: &str, max_age: Duration) -> bool {
+        self.dataref_handler.is_stale(dataref, max_age)
+    }
+
+    pub fn set_shutdown_timeout(&mut self, shutdown_timeout: Duration) {
+        self.shutdown_timeout = shutdown_timeout;
+    }
+
+    pub fn get_shutdown_timeout(&self) -> Duration {
+        self.shutdown_timeout
+    }
+
+    /// Leaves the beacon multicast group, unsubscribes all datarefs and stops the receiving thread.
+    ///
+    /// Unsubscribing is bounded by the shutdown timeout, whatever is left afterwards
+    /// is unsubscribed without waiting for the socket.
     pub async fn shutdown(mut self) {
+        info!("Shutting down session");
+
         // Close beacon, if it exists
-        if let Some(ref beacon) = self.beacon {
-            let _ = beacon.close_beacon();
+        if let Some(ref mut beacon) = self.beacon {
+            if let Err(e) = beacon.close_beacon().await {
+                error!("Failed to close beacon: {}", e);
+            }
         }
 
         // Unsubscribe from all datarefs
-        if let Err(e) = self.unsubscribe_all().await {
-            error!("Failed to unsubscribe from all datarefs: {}", e);
+        let timeout = self.shutdown_timeout;
+        match race(self.unsubscribe_all(), B::sleep(timeout)).await {
+            Either::Left(Ok(())) => {}
+            Either::Left(Err(e)) => error!("Failed to unsubscribe from all datarefs: {}", e),
+            Either::Right(()) => error!("Unsubscribing timed out after {:?}", timeout),
         }
-
-        info!("Shutting down session");
+        // Drop sends whatever was not unsubscribed above
     }
 }
 
-impl Drop for Session {
-    fn drop(&mut self) {
-        if let Some(ref beacon) = self.beacon {
-            // Close beacon, if it exists
-            let _ = beacon.close_beacon();
-        }
+impl<B: Backend, T: Transport> XPlaneClient for Session<B, T> {
+    fn subscribe(&mut self, dataref: &str, frequency: i32, dataref_type: DataRefType)
+        -> impl Future<Output = io::Result<SubscriptionId>> + Send {
+        Session::subscribe(self, dataref, frequency, dataref_type)
+    }
 
-        // Unsubscribe from all datarefs
-        // This tells X-Plane to actually stop sending data
+    fn release(&mut self, id: SubscriptionId) -> impl Future<Output = io::Result<()>> + Send {
+        Session::release(self, id)
+    }
 
-        // Commented out due to async unsubscribe_all
+    fn unsubscribe(&mut self, dataref: &str) -> impl Future<Output = io::Result<()>> + Send {
+        Session::unsubscribe(self, dataref)
+    }
+
+    fn get_dataref(&self, dataref: &str) -> Option<DataRefValueType> {
+        Session::get_dataref(self, dataref)
+    }
 
-        // if let Err(e) = self.unsubscribe_all() {
-        //     error!("Failed to unsubscribe from all datarefs: {}", e);
-        // }
+    fn set_dataref(&self, dataref: &str, value: f32) -> impl Future<Output = io::Result<()>> + Send {
+        Session::set_dataref(self, dataref, value)
+    }
+
+    fn cmd(&self, command: &str) -> impl Future<Output = io::Result<()>> + Send {
+        Session::cmd(self, command)
+    }
+
+    fn alert(&self, message: AlertMessage) -> impl Future<Output = io::Result<()>> + Send {
+        Session::alert(self, message)
+    }
+}
+
+impl<B: Backend, T: Transport> Drop for Session<B, T> {
+    fn drop(&mut self) {
+        // Tell X-Plane to actually stop sending data, without awaiting the socket
+        self.dataref_handler.unsubscribe_all_now(self.xp_sending_socket.as_ref(), &self.xp_receiving_address);
+        self.dataref_handler.stop_run_thread();
 
+        // The beacon leaves its multicast group once dropped
         info!("Session dropped");
     }
 }
\ No newline at end of file
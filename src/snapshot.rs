@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+
+use crate::dataref_type::DataRefValueType;
+
+/// Values of several datarefs taken between two RREF messages.
+///
+/// All values come from whole packets, a snapshot never mixes
+/// a value of one sim frame with a stale value of another dataref from the same packet.
+///
+/// With the `serde` feature, it serializes as its sequence and a map from dataref names
+/// to values in the format of [`DataRefValueType`], ordered by name:
+///
+/// ```json
+/// {"sequence": 42, "values": {"sim/cockpit2/gauges/indicators/altitude_ft_pilot": {"type": "float", "value": 3500.0}}}
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    sequence: u64,
+    values: BTreeMap<String, DataRefValueType>,
+}
+
+impl Snapshot {
+    pub fn new(sequence: u64, values: BTreeMap<String, DataRefValueType>) -> Snapshot {
+        Snapshot { sequence, values }
+    }
+
+    /// Number of RREF messages processed before the snapshot was taken, increases monotonically
+    pub fn get_sequence(&self) -> u64 { self.sequence }
+
+    pub fn get(&self, dataref: &str) -> Option<&DataRefValueType> {
+        self.values.get(dataref)
+    }
+
+    /// Values ordered by dataref name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &DataRefValueType)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn len(&self) -> usize { self.values.len() }
+    pub fn is_empty(&self) -> bool { self.values.is_empty() }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    const JSON: &str = concat!(
+        r#"{"sequence":42,"values":{"#,
+        r#""sim/cockpit2/gauges/indicators/airspeed_kts_pilot":{"type":"float","value":250.5},"#,
+        r#""sim/cockpit2/gauges/indicators/altitude_ft_pilot":{"type":"float","value":3500.0},"#,
+        r#""sim/cockpit2/switches/landing_lights_on":{"type":"bool","value":true}}}"#,
+    );
+
+    fn snapshot() -> Snapshot {
+        // Inserted out of order, the serialized names are still sorted
+        let values = [
+            ("sim/cockpit2/switches/landing_lights_on", DataRefValueType::Bool(true)),
+            ("sim/cockpit2/gauges/indicators/altitude_ft_pilot", DataRefValueType::Float(3500.0)),
+            ("sim/cockpit2/gauges/indicators/airspeed_kts_pilot", DataRefValueType::Float(250.5)),
+        ];
+        Snapshot::new(42, values.into_iter().map(|(name, value)| (name.to_string(), value)).collect())
+    }
+
+    #[test]
+    fn serializes_in_documented_format() {
+        assert_eq!(serde_json::to_string(&snapshot()).unwrap(), JSON);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let snapshot: Snapshot = serde_json::from_str(JSON).unwrap();
+        assert_eq!(snapshot.get_sequence(), 42);
+        assert_eq!(snapshot.len(), 3);
+        assert_eq!(snapshot.get("sim/cockpit2/switches/landing_lights_on"), Some(&DataRefValueType::Bool(true)));
+        assert_eq!(serde_json::to_string(&snapshot).unwrap(), JSON);
+    }
+}
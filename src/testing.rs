@@ -0,0 +1,412 @@
+//! Test doubles for code talking to X-Plane.
+//!
+//! [`MockClient`] replaces the session in application code, [`FakeXPlane`] replaces the
+//! simulator on the other side of a real session.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use log::{debug, error};
+
+use crate::client::XPlaneClient;
+use crate::command_handler::AlertMessage;
+use crate::consts::{
+    ALRT_PREFIX, BEACON_PREFIX, CMND_PREFIX, DREF_PREFIX, RREF_PREFIX, XP_FAKE_TICK_MS, XP_STD_RECEIVE_POLL_MS,
+};
+use crate::dataref::SubscriptionId;
+use crate::dataref_type::{DataRefType, DataRefValueType};
+
+/// [`XPlaneClient`] with scripted dataref values, recording everything sent to the simulator.
+///
+/// Like a session, only subscribed datarefs have a value, [`DataRefValueType::Unknown`]
+/// until one is scripted with [`MockClient::set_value`]. Writes through
+/// [`XPlaneClient::set_dataref`] are recorded and applied to subscribed datarefs.
+#[derive(Default)]
+pub struct MockClient {
+    state: Mutex<MockState>,
+}
+
+#[derive(Default)]
+struct MockState {
+    subscription_counter: u64,
+    subscriptions: HashMap<String, (DataRefType, DataRefValueType)>,
+    /// Dataref of every consumer, a dataref stays subscribed while it has one
+    consumers: HashMap<SubscriptionId, String>,
+    /// Values scripted before the dataref was subscribed
+    scripted: HashMap<String, DataRefValueType>,
+    writes: Vec<(String, f32)>,
+    commands: Vec<String>,
+    alerts: Vec<AlertMessage>,
+}
+
+impl MockClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Sets the value `get_dataref` returns for `dataref`, once it is subscribed
+    pub fn set_value(&self, dataref: &str, value: DataRefValueType) {
+        let mut state = self.state();
+        match state.subscriptions.get_mut(dataref) {
+            Some((_, current)) => *current = value,
+            None => {
+                state.scripted.insert(dataref.to_string(), value);
+            }
+        }
+    }
+
+    pub fn is_subscribed(&self, dataref: &str) -> bool {
+        self.state().subscriptions.contains_key(dataref)
+    }
+
+    /// Dataref writes in the order they were sent
+    pub fn get_writes(&self) -> Vec<(String, f32)> {
+        self.state().writes.clone()
+    }
+
+    /// Commands in the order they were sent
+    pub fn get_commands(&self) -> Vec<String> {
+        self.state().commands.clone()
+    }
+
+    /// Alerts in the order they were sent
+    pub fn get_alerts(&self) -> Vec<AlertMessage> {
+        self.state().alerts.clone()
+    }
+
+    /// Forgets the recorded writes, commands and alerts
+    pub fn clear_recorded(&self) {
+        let mut state = self.state();
+        state.writes.clear();
+        state.commands.clear();
+        state.alerts.clear();
+    }
+}
+
+impl XPlaneClient for MockClient {
+    async fn subscribe(&mut self, dataref: &str, _frequency: i32, dataref_type: DataRefType) -> io::Result<SubscriptionId> {
+        let mut state = self.state();
+        let value = state.scripted.remove(dataref).unwrap_or(DataRefValueType::Unknown);
+        state.subscriptions.entry(dataref.to_string()).or_insert((dataref_type, value));
+
+        state.subscription_counter += 1;
+        let id = SubscriptionId(state.subscription_counter);
+        state.consumers.insert(id, dataref.to_string());
+        Ok(id)
+    }
+
+    async fn release(&mut self, id: SubscriptionId) -> io::Result<()> {
+        let mut state = self.state();
+        let dataref = match state.consumers.remove(&id) {
+            Some(e) => e,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Subscription not found")),
+        };
+        if !state.consumers.values().any(|e| *e == dataref) {
+            state.subscriptions.remove(&dataref);
+        }
+        Ok(())
+    }
+
+    async fn unsubscribe(&mut self, dataref: &str) -> io::Result<()> {
+        let mut state = self.state();
+        state.consumers.retain(|_, e| e != dataref);
+        match state.subscriptions.remove(dataref) {
+            Some(_) => Ok(()),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "Dataref not found")),
+        }
+    }
+
+    fn get_dataref(&self, dataref: &str) -> Option<DataRefValueType> {
+        self.state().subscriptions.get(dataref).map(|(_, value)| *value)
+    }
+
+    async fn set_dataref(&self, dataref: &str, value: f32) -> io::Result<()> {
+        let mut state = self.state();
+        if let Some((value_type, current)) = state.subscriptions.get_mut(dataref) {
+            *current = value_type.convert(value);
+        }
+        state.writes.push((dataref.to_string(), value));
+        Ok(())
+    }
+
+    async fn cmd(&self, command: &str) -> io::Result<()> {
+        self.state().commands.push(command.to_string());
+        Ok(())
+    }
+
+    async fn alert(&self, message: AlertMessage) -> io::Result<()> {
+        self.state().alerts.push(message);
+        Ok(())
+    }
+}
+
+/// In-process X-Plane speaking the UDP protocol on a local socket.
+///
+/// RREF subscriptions are answered at their requested frequency from a value table, only for
+/// datarefs present in the table, like X-Plane ignores datarefs it does not know. DREF writes
+/// are applied to the table, CMND and ALRT packets are recorded. The fake runs on its own
+/// threads, so it works with every backend and stops once dropped.
+pub struct FakeXPlane {
+    socket: UdpSocket,
+    /// Socket the RREF values are sent from, the receiving socket unless started split
+    sending_socket: UdpSocket,
+    state: Arc<Mutex<FakeState>>,
+    stop: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct FakeState {
+    values: HashMap<String, f32>,
+    /// Keyed by the address the request came from and its index, like X-Plane does
+    subscriptions: HashMap<(SocketAddr, i32), FakeSubscription>,
+    writes: Vec<(String, f32)>,
+    commands: Vec<String>,
+    alerts: Vec<AlertMessage>,
+}
+
+struct FakeSubscription {
+    name: String,
+    interval: Duration,
+    next_due: Instant,
+}
+
+impl FakeXPlane {
+    /// Starts the fake on a free port of the loopback interface
+    pub fn start() -> io::Result<Self> {
+        Self::start_on(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0).into())
+    }
+
+    /// Starts the fake on `address`, used for both directions
+    pub fn start_on(address: SocketAddr) -> io::Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        let sending_socket = socket.try_clone()?;
+        Self::start_with_sockets(socket, sending_socket)
+    }
+
+    /// Starts the fake receiving on `receiving` and sending the RREF values from `sending`,
+    /// like X-Plane does with its receiving and sending ports
+    pub fn start_split(receiving: SocketAddr, sending: SocketAddr) -> io::Result<Self> {
+        Self::start_with_sockets(UdpSocket::bind(receiving)?, UdpSocket::bind(sending)?)
+    }
+
+    fn start_with_sockets(socket: UdpSocket, sending_socket: UdpSocket) -> io::Result<Self> {
+        socket.set_read_timeout(Some(Duration::from_millis(XP_STD_RECEIVE_POLL_MS)))?;
+
+        let mut fake = FakeXPlane {
+            socket,
+            sending_socket,
+            state: Arc::new(Mutex::new(FakeState::default())),
+            stop: Arc::new(AtomicBool::new(false)),
+            threads: Vec::new(),
+        };
+
+        let socket = fake.socket.try_clone()?;
+        let state = fake.state.clone();
+        let stop = fake.stop.clone();
+        fake.spawn("xplane_udp fake receiver", move || Self::receive_loop(socket, state, stop))?;
+
+        let socket = fake.sending_socket.try_clone()?;
+        let state = fake.state.clone();
+        let stop = fake.stop.clone();
+        fake.spawn("xplane_udp fake sender", move || Self::send_loop(socket, state, stop))?;
+
+        debug!("Fake X-Plane listening on {}", fake.get_address()?);
+        Ok(fake)
+    }
+
+    /// Sends a BECN beacon pointing at this fake to the multicast `group` every `interval`.
+    ///
+    /// Sessions created from the beacon expect the values from
+    /// [`XP_DEFAULT_SENDING_PORT`](crate::consts::XP_DEFAULT_SENDING_PORT)
+    /// on the source address of the beacon, start the fake with [`FakeXPlane::start_split`]
+    /// on the unspecified address for them to receive values.
+    pub fn emit_beacon(&mut self, group: SocketAddrV4, interval: Duration) -> io::Result<()> {
+        let port = self.get_address()?.port();
+        let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))?;
+        socket.set_multicast_loop_v4(true)?;
+
+        let stop = self.stop.clone();
+        self.spawn("xplane_udp fake beacon", move || {
+            let message = Self::beacon_message(port);
+            while !stop.load(Ordering::Relaxed) {
+                if let Err(e) = socket.send_to(&message, group) {
+                    error!("Fake X-Plane failed to send beacon: {}", e);
+                }
+                thread::sleep(interval);
+            }
+        })
+    }
+
+    fn spawn<F: FnOnce() + Send + 'static>(&mut self, name: &str, f: F) -> io::Result<()> {
+        let handle = thread::Builder::new().name(name.to_string()).spawn(f)?;
+        self.threads.push(handle);
+        Ok(())
+    }
+
+    fn state(&self) -> MutexGuard<'_, FakeState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Address the fake receives on, the receiving address of a session
+    pub fn get_address(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Address the RREF values are sent from, the sending address of a session
+    pub fn get_sending_address(&self) -> io::Result<SocketAddr> {
+        self.sending_socket.local_addr()
+    }
+
+    pub fn set_value(&self, dataref: &str, value: f32) {
+        self.state().values.insert(dataref.to_string(), value);
+    }
+
+    pub fn get_value(&self, dataref: &str) -> Option<f32> {
+        self.state().values.get(dataref).copied()
+    }
+
+    /// Names and frequencies of the active RREF subscriptions
+    pub fn get_subscriptions(&self) -> Vec<(String, i32)> {
+        self.state().subscriptions.values()
+            .map(|e| (e.name.clone(), (1.0 / e.interval.as_secs_f64()).round() as i32))
+            .collect()
+    }
+
+    /// DREF writes in the order they arrived
+    pub fn get_writes(&self) -> Vec<(String, f32)> {
+        self.state().writes.clone()
+    }
+
+    /// CMND packets in the order they arrived
+    pub fn get_commands(&self) -> Vec<String> {
+        self.state().commands.clone()
+    }
+
+    /// ALRT packets in the order they arrived
+    pub fn get_alerts(&self) -> Vec<AlertMessage> {
+        self.state().alerts.clone()
+    }
+
+    fn receive_loop(socket: UdpSocket, state: Arc<Mutex<FakeState>>, stop: Arc<AtomicBool>) {
+        let mut buffer = [0; 2048];
+        while !stop.load(Ordering::Relaxed) {
+            match socket.recv_from(&mut buffer) {
+                Ok((received, source)) => {
+                    let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+                    Self::handle_message(&mut state, &buffer[..received], source);
+                }
+                Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+                Err(e) => error!("Fake X-Plane failed to receive: {}", e),
+            }
+        }
+    }
+
+    fn handle_message(state: &mut FakeState, data: &[u8], source: SocketAddr) {
+        if data.len() < 5 {
+            debug!("Fake X-Plane ignored a {} byte message", data.len());
+            return;
+        }
+        let (prefix, body) = (&data[..4], &data[5..]);
+
+        if prefix == RREF_PREFIX && body.len() >= 8 {
+            let frequency = i32::from_le_bytes(body[0..4].try_into().unwrap());
+            let index = i32::from_le_bytes(body[4..8].try_into().unwrap());
+            if frequency <= 0 {
+                state.subscriptions.remove(&(source, index));
+            } else {
+                state.subscriptions.insert((source, index), FakeSubscription {
+                    name: null_terminated(&body[8..]),
+                    interval: Duration::from_secs_f64(1.0 / frequency as f64),
+                    next_due: Instant::now(),
+                });
+            }
+        } else if prefix == DREF_PREFIX && body.len() >= 4 {
+            let value = f32::from_le_bytes(body[0..4].try_into().unwrap());
+            let name = null_terminated(&body[4..]);
+            state.values.insert(name.clone(), value);
+            state.writes.push((name, value));
+        } else if prefix == CMND_PREFIX {
+            state.commands.push(null_terminated(body));
+        } else if prefix == ALRT_PREFIX {
+            let mut alert = AlertMessage::default();
+            for (i, line) in body.chunks(240).take(4).enumerate() {
+                let _ = alert.set_line(&null_terminated(line), i);
+            }
+            state.alerts.push(alert);
+        } else {
+            debug!("Fake X-Plane ignored a message with prefix {:?}", String::from_utf8_lossy(prefix));
+        }
+    }
+
+    fn send_loop(socket: UdpSocket, state: Arc<Mutex<FakeState>>, stop: Arc<AtomicBool>) {
+        let tick = Duration::from_millis(XP_FAKE_TICK_MS);
+        while !stop.load(Ordering::Relaxed) {
+            let now = Instant::now();
+            let mut messages: HashMap<SocketAddr, Vec<u8>> = HashMap::new();
+            {
+                let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+                let FakeState { values, subscriptions, .. } = &mut *state;
+                for ((destination, index), subscription) in subscriptions.iter_mut() {
+                    if subscription.next_due > now {
+                        continue;
+                    }
+                    subscription.next_due = now + subscription.interval;
+                    if let Some(value) = values.get(&subscription.name) {
+                        let message = messages.entry(*destination).or_insert_with(|| {
+                            let mut message = RREF_PREFIX.to_vec();
+                            message.push(0);
+                            message
+                        });
+                        message.extend_from_slice(&index.to_le_bytes());
+                        message.extend_from_slice(&value.to_le_bytes());
+                    }
+                }
+            }
+
+            for (destination, message) in messages {
+                if let Err(e) = socket.send_to(&message, destination) {
+                    error!("Fake X-Plane failed to send RREF values to {}: {}", destination, e);
+                }
+            }
+            thread::sleep(tick);
+        }
+    }
+
+    fn beacon_message(port: u16) -> Vec<u8> {
+        // Beacon major and minor version, X-Plane as the host, version 12.1.4, master role
+        let mut message = BEACON_PREFIX.to_vec();
+        message.push(0);
+        message.extend_from_slice(&[1, 2]);
+        message.extend_from_slice(&1i32.to_le_bytes());
+        message.extend_from_slice(&121400i32.to_le_bytes());
+        message.extend_from_slice(&1u32.to_le_bytes());
+        message.extend_from_slice(&port.to_le_bytes());
+        message.extend_from_slice(b"FakeXPlane\0");
+        message
+    }
+}
+
+impl Drop for FakeXPlane {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        for handle in self.threads.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn null_terminated(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
@@ -0,0 +1,99 @@
+//! Datagram transport carrying the X-Plane protocol.
+//!
+//! [`crate::session::Session`], [`crate::dataref_handler::DataRefHandler`] and
+//! [`crate::command_handler::CommandHandler`] only send and receive datagrams through
+//! [`Transport`]. The UDP sockets of the backends implement it, [`ChannelTransport`] connects
+//! two ends in memory, e.g. a session and a test double.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use crate::utils::Notifier;
+
+pub trait Transport: Send + Sync + 'static {
+    /// Sends a single datagram to `target`
+    fn send_to(&self, buf: &[u8], target: SocketAddr) -> impl Future<Output = io::Result<usize>> + Send;
+
+    /// Sends without waiting, for teardown paths that cannot await
+    fn try_send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize>;
+
+    /// Receives a single datagram, a datagram larger than `buf` is truncated
+    fn recv_from(&self, buf: &mut [u8]) -> impl Future<Output = io::Result<(usize, SocketAddr)>> + Send;
+
+    fn local_addr(&self) -> io::Result<SocketAddr>;
+
+    /// Restricts the transport to `address`, transports without the notion of a peer ignore it
+    fn connect(&self, address: SocketAddr) -> impl Future<Output = io::Result<()>> + Send {
+        let _ = address;
+        async { Ok(()) }
+    }
+}
+
+/// One end of an in-memory datagram channel, created with [`ChannelTransport::pair`].
+///
+/// Datagrams are delivered to the other end regardless of their target address,
+/// the receiver sees the address of the sending end as their source.
+pub struct ChannelTransport {
+    address: SocketAddr,
+    inbox: Arc<Mailbox>,
+    outbox: Arc<Mailbox>,
+}
+
+#[derive(Default)]
+struct Mailbox {
+    datagrams: Mutex<VecDeque<(Vec<u8>, SocketAddr)>>,
+    notifier: Notifier,
+}
+
+impl ChannelTransport {
+    /// Connected ends pretending to be bound to `a` and `b`
+    pub fn pair(a: SocketAddr, b: SocketAddr) -> (Self, Self) {
+        let a_inbox = Arc::new(Mailbox::default());
+        let b_inbox = Arc::new(Mailbox::default());
+
+        let a_end = ChannelTransport { address: a, inbox: a_inbox.clone(), outbox: b_inbox.clone() };
+        let b_end = ChannelTransport { address: b, inbox: b_inbox, outbox: a_inbox };
+        (a_end, b_end)
+    }
+
+    fn pop(&self, buf: &mut [u8]) -> Option<(usize, SocketAddr)> {
+        let (datagram, source) = self.inbox.datagrams.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front()?;
+        let len = datagram.len().min(buf.len());
+        buf[..len].copy_from_slice(&datagram[..len]);
+        Some((len, source))
+    }
+}
+
+impl Transport for ChannelTransport {
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        self.try_send_to(buf, target)
+    }
+
+    fn try_send_to(&self, buf: &[u8], _target: SocketAddr) -> io::Result<usize> {
+        self.outbox.datagrams.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push_back((buf.to_vec(), self.address));
+        self.outbox.notifier.notify_waiters();
+        Ok(buf.len())
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        loop {
+            // Registered before checking the queue, so a datagram pushed in between still wakes us
+            let notified = self.inbox.notifier.notified();
+            if let Some(received) = self.pop(buf) {
+                return Ok(received);
+            }
+            notified.await;
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.address)
+    }
+}
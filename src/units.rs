@@ -0,0 +1,242 @@
+//! Units of measure of dataref values.
+//!
+//! A subscription declares the unit X-Plane sends, e.g. with
+//! [`Session::subscribe_with_unit`](crate::session::Session::subscribe_with_unit),
+//! and callers read the value in the unit they need with `session.get_as::<Feet>(...)`.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Physical quantity of a unit, only units of the same dimension convert into each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    Length,
+    Speed,
+    Angle,
+    Mass,
+    Temperature,
+    Pressure,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Meters,
+    Feet,
+    NauticalMiles,
+    Kilometers,
+    MetersPerSecond,
+    Knots,
+    FeetPerMinute,
+    KilometersPerHour,
+    Radians,
+    Degrees,
+    Kilograms,
+    Pounds,
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+    Pascals,
+    Hectopascals,
+    InchesOfMercury,
+}
+
+impl Unit {
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Unit::Meters | Unit::Feet | Unit::NauticalMiles | Unit::Kilometers => Dimension::Length,
+            Unit::MetersPerSecond | Unit::Knots | Unit::FeetPerMinute | Unit::KilometersPerHour => Dimension::Speed,
+            Unit::Radians | Unit::Degrees => Dimension::Angle,
+            Unit::Kilograms | Unit::Pounds => Dimension::Mass,
+            Unit::Celsius | Unit::Fahrenheit | Unit::Kelvin => Dimension::Temperature,
+            Unit::Pascals | Unit::Hectopascals | Unit::InchesOfMercury => Dimension::Pressure,
+        }
+    }
+
+    /// `(factor, offset)` taking a value in this unit to the SI unit of its dimension
+    fn to_si(self) -> (f64, f64) {
+        match self {
+            Unit::Meters => (1.0, 0.0),
+            Unit::Feet => (0.3048, 0.0),
+            Unit::NauticalMiles => (1852.0, 0.0),
+            Unit::Kilometers => (1000.0, 0.0),
+            Unit::MetersPerSecond => (1.0, 0.0),
+            Unit::Knots => (1852.0 / 3600.0, 0.0),
+            Unit::FeetPerMinute => (0.3048 / 60.0, 0.0),
+            Unit::KilometersPerHour => (1000.0 / 3600.0, 0.0),
+            Unit::Radians => (1.0, 0.0),
+            Unit::Degrees => (std::f64::consts::PI / 180.0, 0.0),
+            Unit::Kilograms => (1.0, 0.0),
+            Unit::Pounds => (0.453_592_37, 0.0),
+            Unit::Kelvin => (1.0, 0.0),
+            Unit::Celsius => (1.0, 273.15),
+            Unit::Fahrenheit => (5.0 / 9.0, 273.15 - 32.0 * 5.0 / 9.0),
+            Unit::Pascals => (1.0, 0.0),
+            Unit::Hectopascals => (100.0, 0.0),
+            Unit::InchesOfMercury => (3386.389, 0.0),
+        }
+    }
+
+    /// Converts `value` from this unit to `target`, `None` if their dimensions differ
+    pub fn convert(&self, value: f64, target: Unit) -> Option<f64> {
+        if self.dimension() != target.dimension() {
+            return None;
+        }
+        let (factor, offset) = self.to_si();
+        let (target_factor, target_offset) = target.to_si();
+        Some((value * factor + offset - target_offset) / target_factor)
+    }
+
+    /// Short symbol, e.g. `ft` or `kt`
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Meters => "m",
+            Unit::Feet => "ft",
+            Unit::NauticalMiles => "nm",
+            Unit::Kilometers => "km",
+            Unit::MetersPerSecond => "m/s",
+            Unit::Knots => "kt",
+            Unit::FeetPerMinute => "ft/min",
+            Unit::KilometersPerHour => "km/h",
+            Unit::Radians => "rad",
+            Unit::Degrees => "deg",
+            Unit::Kilograms => "kg",
+            Unit::Pounds => "lb",
+            Unit::Celsius => "degC",
+            Unit::Fahrenheit => "degF",
+            Unit::Kelvin => "K",
+            Unit::Pascals => "Pa",
+            Unit::Hectopascals => "hPa",
+            Unit::InchesOfMercury => "inHg",
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl FromStr for Unit {
+    type Err = ();
+
+    /// Parses the symbols of [`Unit::symbol`] and the spellings used in `DataRefs.txt`,
+    /// case-insensitive
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unit = match s.trim().to_ascii_lowercase().as_str() {
+            "m" | "meter" | "meters" | "metres" => Unit::Meters,
+            "ft" | "feet" | "foot" => Unit::Feet,
+            "nm" | "nmi" | "nautical miles" => Unit::NauticalMiles,
+            "km" | "kilometers" | "kilometres" => Unit::Kilometers,
+            "m/s" | "mtr/sec" | "meters/sec" | "meters/second" => Unit::MetersPerSecond,
+            "kt" | "kts" | "knots" => Unit::Knots,
+            "ft/min" | "fpm" | "feet/minute" => Unit::FeetPerMinute,
+            "km/h" | "kph" => Unit::KilometersPerHour,
+            "rad" | "radians" => Unit::Radians,
+            "deg" | "degree" | "degrees" | "degm" | "degt" => Unit::Degrees,
+            "kg" | "kgs" | "kilograms" => Unit::Kilograms,
+            "lb" | "lbs" | "pounds" => Unit::Pounds,
+            "degc" | "celsius" => Unit::Celsius,
+            "degf" | "fahrenheit" => Unit::Fahrenheit,
+            "k" | "kelvin" => Unit::Kelvin,
+            "pa" | "pascals" => Unit::Pascals,
+            "hpa" | "mb" | "mbar" | "millibars" => Unit::Hectopascals,
+            "inhg" | "inches hg" | "inches_hg" => Unit::InchesOfMercury,
+            _ => return Err(()),
+        };
+        Ok(unit)
+    }
+}
+
+/// Type-level unit for [`Session::get_as`](crate::session::Session::get_as)
+pub trait UnitOf {
+    const UNIT: Unit;
+}
+
+macro_rules! unit_markers {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[doc = concat!("Marker of [`Unit::", stringify!($name), "`]")]
+            #[derive(Debug, Clone, Copy)]
+            pub struct $name;
+
+            impl UnitOf for $name {
+                const UNIT: Unit = Unit::$name;
+            }
+        )*
+    };
+}
+
+unit_markers!(
+    Meters, Feet, NauticalMiles, Kilometers,
+    MetersPerSecond, Knots, FeetPerMinute, KilometersPerHour,
+    Radians, Degrees,
+    Kilograms, Pounds,
+    Celsius, Fahrenheit, Kelvin,
+    Pascals, Hectopascals, InchesOfMercury,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Unit; 18] = [
+        Unit::Meters, Unit::Feet, Unit::NauticalMiles, Unit::Kilometers,
+        Unit::MetersPerSecond, Unit::Knots, Unit::FeetPerMinute, Unit::KilometersPerHour,
+        Unit::Radians, Unit::Degrees,
+        Unit::Kilograms, Unit::Pounds,
+        Unit::Celsius, Unit::Fahrenheit, Unit::Kelvin,
+        Unit::Pascals, Unit::Hectopascals, Unit::InchesOfMercury,
+    ];
+
+    fn assert_converts(value: f64, from: Unit, to: Unit, expected: f64, tolerance: f64) {
+        let converted = from.convert(value, to).unwrap();
+        assert!((converted - expected).abs() < tolerance, "{} {} is {} {}, expected {}", value, from, converted, to, expected);
+    }
+
+    #[test]
+    fn temperatures_convert_with_offsets() {
+        assert_converts(32.0, Unit::Fahrenheit, Unit::Celsius, 0.0, 1e-9);
+        assert_converts(212.0, Unit::Fahrenheit, Unit::Celsius, 100.0, 1e-9);
+        assert_converts(-40.0, Unit::Celsius, Unit::Fahrenheit, -40.0, 1e-9);
+        assert_converts(0.0, Unit::Celsius, Unit::Kelvin, 273.15, 1e-9);
+        assert_converts(0.0, Unit::Kelvin, Unit::Fahrenheit, -459.67, 1e-9);
+    }
+
+    #[test]
+    fn known_values() {
+        assert_converts(1.0, Unit::Knots, Unit::MetersPerSecond, 0.514_444, 1e-6);
+        assert_converts(29.92, Unit::InchesOfMercury, Unit::Hectopascals, 1013.2, 0.05);
+        assert_converts(10000.0, Unit::Feet, Unit::Meters, 3048.0, 1e-9);
+        assert_converts(1000.0, Unit::FeetPerMinute, Unit::MetersPerSecond, 5.08, 1e-9);
+        assert_converts(180.0, Unit::Degrees, Unit::Radians, std::f64::consts::PI, 1e-12);
+    }
+
+    #[test]
+    fn round_trips_within_a_dimension() {
+        for from in ALL {
+            for to in ALL.iter().filter(|e| e.dimension() == from.dimension()) {
+                let back = from.convert(123.456, *to).and_then(|e| to.convert(e, from)).unwrap();
+                assert!((back - 123.456).abs() < 1e-9, "{} -> {} -> {}", from, to, from);
+            }
+        }
+    }
+
+    #[test]
+    fn different_dimensions_do_not_convert() {
+        assert_eq!(Unit::Feet.convert(1.0, Unit::Knots), None);
+        assert_eq!(Unit::Celsius.convert(1.0, Unit::Pascals), None);
+    }
+
+    #[test]
+    fn parses_symbols_and_spellings() {
+        for unit in ALL {
+            assert_eq!(unit.symbol().parse(), Ok(unit));
+        }
+        assert_eq!(" Knots ".parse(), Ok(Unit::Knots));
+        assert_eq!("mtr/sec".parse(), Ok(Unit::MetersPerSecond));
+        assert_eq!("inches hg".parse(), Ok(Unit::InchesOfMercury));
+        assert_eq!("furlongs".parse::<Unit>(), Err(()));
+        assert_eq!("".parse::<Unit>(), Err(()));
+    }
+}
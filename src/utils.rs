@@ -0,0 +1,118 @@
+use std::future::{poll_fn, Future};
+use std::pin::{pin, Pin};
+use std::sync::Mutex;
+use std::task::{Context, Poll, Waker};
+
+pub(crate) enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// Polls both futures and returns the output of the first one to complete, `a` wins ties
+pub(crate) async fn race<A, B>(a: A, b: B) -> Either<A::Output, B::Output>
+where
+    A: Future,
+    B: Future,
+{
+    let mut a = pin!(a);
+    let mut b = pin!(b);
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = a.as_mut().poll(cx) {
+            return Poll::Ready(Either::Left(output));
+        }
+        if let Poll::Ready(output) = b.as_mut().poll(cx) {
+            return Poll::Ready(Either::Right(output));
+        }
+        Poll::Pending
+    }).await
+}
+
+/// Runtime independent wake-up of all current waiters
+#[derive(Default)]
+pub(crate) struct Notifier {
+    state: Mutex<NotifierState>,
+}
+
+#[derive(Default)]
+struct NotifierState {
+    generation: u64,
+    wakers: Vec<Waker>,
+}
+
+impl Notifier {
+    pub(crate) fn notify_waiters(&self) {
+        let wakers = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.generation += 1;
+            std::mem::take(&mut state.wakers)
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Completes on the next [`Notifier::notify_waiters`] after this call,
+    /// even if the future is polled for the first time only after the notification
+    pub(crate) fn notified(&self) -> Notified<'_> {
+        let generation = self.state.lock().unwrap_or_else(|e| e.into_inner()).generation;
+        Notified { notifier: self, generation }
+    }
+}
+
+pub(crate) struct Notified<'a> {
+    notifier: &'a Notifier,
+    generation: u64,
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.notifier.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.generation != self.generation {
+            return Poll::Ready(());
+        }
+        if !state.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+/// Number of single character insertions, deletions and substitutions turning `a` into `b`
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("flaps", "flaps"), 0);
+        assert_eq!(edit_distance("", "gear"), 4);
+        assert_eq!(edit_distance("gear", ""), 4);
+        assert_eq!(edit_distance("servo", "servos"), 1);
+        assert_eq!(edit_distance("servos", "servo"), 1);
+        assert_eq!(edit_distance("flaps_up", "flaps_dn"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn edit_distance_is_symmetric() {
+        assert_eq!(edit_distance("landing_gear_toggle", "gear_toggle"), edit_distance("gear_toggle", "landing_gear_toggle"));
+    }
+}
@@ -0,0 +1,99 @@
+//! Aircraft detection and profile switching against [`FakeXPlane`].
+#![cfg(all(feature = "profiles", any(feature = "tokio", feature = "std")))]
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use xplane_udp::aircraft::{AircraftIdentity, AircraftSwitcher};
+use xplane_udp::consts::{XP_AIRCRAFT_ICAO_DATAREF, XP_AIRCRAFT_ICAO_LEN, XP_AIRCRAFT_PATH_DATAREF,
+                         XP_AIRCRAFT_PATH_LEN, XP_AIRCRAFT_SETTLE_MS};
+use xplane_udp::profile::AircraftProfile;
+use xplane_udp::session::Session;
+use xplane_udp::testing::FakeXPlane;
+
+mod common;
+
+use common::{block_on, eventually};
+
+const ZIBO: &str = r#"
+name = "Zibo 737-800"
+aircraft = ["B738"]
+"#;
+
+/// Sets the byte array dataref `dataref` to `text`, padded with NULs like X-Plane does
+fn set_string(fake: &FakeXPlane, dataref: &str, len: usize, text: &str) {
+    let mut bytes = text.bytes().chain(std::iter::repeat(0));
+    for i in 0..len {
+        fake.set_value(&format!("{}[{}]", dataref, i), bytes.next().unwrap() as f32);
+    }
+}
+
+fn load_aircraft(fake: &FakeXPlane, icao: &str, path: &str) {
+    set_string(fake, XP_AIRCRAFT_ICAO_DATAREF, XP_AIRCRAFT_ICAO_LEN, icao);
+    set_string(fake, XP_AIRCRAFT_PATH_DATAREF, XP_AIRCRAFT_PATH_LEN, path);
+}
+
+#[test]
+fn switches_once_after_the_identity_settled() {
+    let fake = FakeXPlane::start().unwrap();
+    let address = fake.get_address().unwrap();
+    load_aircraft(&fake, "B738", "Aircraft/B737-800X/b738_4k.acf");
+
+    block_on(async {
+        let mut session = Session::manual(address, address).await.unwrap();
+        session.run().await.unwrap();
+        let mut switcher = AircraftSwitcher::new();
+        switcher.register(AircraftProfile::from_toml(ZIBO).unwrap());
+        switcher.start(&mut session).await.unwrap();
+        switcher.wait_for_change(&mut session, Duration::from_secs(5)).await.unwrap();
+
+        // X-Plane updates the ICAO and path bytes separately while loading an aircraft
+        set_string(&fake, XP_AIRCRAFT_ICAO_DATAREF, XP_AIRCRAFT_ICAO_LEN, "A320");
+        thread::sleep(Duration::from_millis(300));
+        set_string(&fake, XP_AIRCRAFT_PATH_DATAREF, XP_AIRCRAFT_PATH_LEN, "Aircraft/A320/a320.acf");
+
+        // Poll well past the settle time, the identity is only sent once a second
+        let started = Instant::now();
+        let mut changes = Vec::new();
+        while started.elapsed() < Duration::from_millis(XP_AIRCRAFT_SETTLE_MS + 3000) {
+            if let Some(changed) = switcher.update(&mut session).await {
+                changes.push((started.elapsed(), changed));
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        assert_eq!(changes.len(), 1, "{:?}", changes);
+        let (after, changed) = &changes[0];
+        assert!(*after >= Duration::from_millis(XP_AIRCRAFT_SETTLE_MS), "switched after {:?}", after);
+        assert_eq!(changed.get_previous(), Some(&AircraftIdentity::new("B738", "Aircraft/B737-800X/b738_4k.acf")));
+        assert_eq!(changed.get_current(), &AircraftIdentity::new("A320", "Aircraft/A320/a320.acf"));
+        assert_eq!(changed.get_profile(), None);
+        assert!(switcher.get_active_profile().is_none());
+    });
+}
+
+#[test]
+fn wait_for_change_matches_the_loaded_aircraft() {
+    let fake = FakeXPlane::start().unwrap();
+    let address = fake.get_address().unwrap();
+    load_aircraft(&fake, "B738", "Aircraft/B737-800X/b738_4k.acf");
+
+    block_on(async {
+        let mut session = Session::manual(address, address).await.unwrap();
+        session.run().await.unwrap();
+        let mut switcher = AircraftSwitcher::new();
+        switcher.register(AircraftProfile::from_toml(ZIBO).unwrap());
+        switcher.start(&mut session).await.unwrap();
+        assert!(eventually(|| fake.get_subscriptions().len() == XP_AIRCRAFT_ICAO_LEN + XP_AIRCRAFT_PATH_LEN));
+
+        let changed = switcher.wait_for_change(&mut session, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(changed.get_previous(), None);
+        assert_eq!(changed.get_current(), &AircraftIdentity::new("B738", "Aircraft/B737-800X/b738_4k.acf"));
+        assert_eq!(changed.get_profile(), Some("Zibo 737-800"));
+        assert_eq!(switcher.get_active_profile().map(|e| e.get_name()), Some("Zibo 737-800"));
+
+        switcher.stop(&mut session).await;
+        assert!(eventually(|| fake.get_subscriptions().is_empty()));
+        assert_eq!(switcher.get_identity(), None);
+    });
+}
@@ -0,0 +1,38 @@
+//! Alias subscriptions against [`FakeXPlane`].
+#![cfg(any(feature = "tokio", feature = "std"))]
+
+use std::io;
+
+use xplane_udp::alias::{AliasRegistry, AliasTarget};
+use xplane_udp::dataref_type::DataRefType;
+use xplane_udp::session::Session;
+use xplane_udp::testing::FakeXPlane;
+
+mod common;
+
+use common::{block_on, eventually, ALTITUDE, HEADING};
+
+#[test]
+fn resubscribe_follows_changed_mapping() {
+    let fake = FakeXPlane::start().unwrap();
+    let address = fake.get_address().unwrap();
+
+    block_on(async {
+        let mut session = Session::manual(address, address).await.unwrap();
+        session.run().await.unwrap();
+        let mut aliases = AliasRegistry::empty();
+        aliases.set_aircraft([("selected".to_string(), AliasTarget::dataref(ALTITUDE, DataRefType::Float))]);
+
+        let id = aliases.subscribe(&mut session, "selected", 10).await.unwrap();
+        assert!(eventually(|| fake.get_subscriptions() == vec![(ALTITUDE.to_string(), 10)]));
+
+        aliases.set_aircraft([("selected".to_string(), AliasTarget::dataref(HEADING, DataRefType::Float))]);
+        aliases.resubscribe(&mut session).await.unwrap();
+        assert!(eventually(|| fake.get_subscriptions() == vec![(HEADING.to_string(), 10)]));
+
+        // The id returned by subscribe still identifies the moved subscription
+        aliases.release(&mut session, id).await.unwrap();
+        assert!(eventually(|| fake.get_subscriptions().is_empty()));
+        assert_eq!(aliases.release(&mut session, id).await.unwrap_err().kind(), io::ErrorKind::NotFound);
+    });
+}
@@ -0,0 +1,53 @@
+//! Paced batch subscriptions against [`FakeXPlane`].
+#![cfg(any(feature = "tokio", feature = "std"))]
+
+use std::io;
+use std::time::Duration;
+
+use xplane_udp::consts::XP_BATCH_MAX_RETRIES;
+use xplane_udp::dataref::SubscriptionStatus;
+use xplane_udp::dataref_type::DataRefType;
+use xplane_udp::testing::FakeXPlane;
+
+mod common;
+
+use common::{connect, eventually, ALTITUDE, HEADING};
+
+const UNKNOWN: &str = "sim/does/not/exist";
+
+#[test]
+fn subscribe_many_retries_unanswered_datarefs() {
+    let fake = FakeXPlane::start().unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    fake.set_value(HEADING, 90.0);
+    let mut session = connect(&fake);
+    session.set_confirm_timeout(Duration::from_millis(200));
+
+    let results = session.subscribe_many(&[
+        (ALTITUDE, 10, DataRefType::Float),
+        (UNKNOWN, 10, DataRefType::Float),
+        (HEADING, 10, DataRefType::Float),
+    ]);
+
+    let datarefs: Vec<&str> = results.iter().map(|e| e.get_dataref()).collect();
+    assert_eq!(datarefs, vec![ALTITUDE, UNKNOWN, HEADING]);
+    assert!(results[0].is_ok() && results[2].is_ok());
+    assert_eq!(results[0].get_attempts(), 1);
+    assert_eq!(results[1].get_result().as_ref().unwrap_err().kind(), io::ErrorKind::TimedOut);
+    assert_eq!(results[1].get_attempts(), XP_BATCH_MAX_RETRIES + 1);
+    assert_eq!(session.subscription_status(UNKNOWN), Some(SubscriptionStatus::NoResponse));
+}
+
+#[test]
+fn unsubscribe_many_reports_each_dataref() {
+    let fake = FakeXPlane::start().unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    let mut session = connect(&fake);
+    session.subscribe(ALTITUDE, 10, DataRefType::Float).unwrap();
+    assert!(eventually(|| fake.get_subscriptions().len() == 1));
+
+    let results = session.unsubscribe_many(&[ALTITUDE, HEADING]);
+    assert!(results[0].is_ok());
+    assert_eq!(results[1].get_result().as_ref().unwrap_err().kind(), io::ErrorKind::NotFound);
+    assert!(eventually(|| fake.get_subscriptions().is_empty()));
+}
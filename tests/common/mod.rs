@@ -0,0 +1,37 @@
+//! Helpers shared by the integration tests running against [`FakeXPlane`].
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use xplane_udp::backend::{Backend, DefaultBackend, Executor};
+use xplane_udp::blocking::Session;
+use xplane_udp::testing::FakeXPlane;
+
+pub const ALTITUDE: &str = "sim/cockpit2/autopilot/altitude_dial_ft";
+pub const HEADING: &str = "sim/cockpit2/autopilot/heading_dial_deg_mag_pilot";
+
+/// Blocking session sending to and receiving from `fake`, already running
+pub fn connect(fake: &FakeXPlane) -> Session {
+    let address = fake.get_address().unwrap();
+    let mut session = Session::manual(address, address).unwrap();
+    session.run().unwrap();
+    session
+}
+
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    Executor::block_on(&DefaultBackend::executor().unwrap(), future)
+}
+
+/// Polls `condition` until it holds, the fake applies packets on its own thread
+pub fn eventually<F: Fn() -> bool>(condition: F) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    condition()
+}
@@ -0,0 +1,35 @@
+//! Subscription confirmation against [`FakeXPlane`].
+#![cfg(any(feature = "tokio", feature = "std"))]
+
+use std::io;
+use std::time::Duration;
+
+use xplane_udp::dataref::SubscriptionStatus;
+use xplane_udp::dataref_type::{DataRefType, DataRefValueType};
+use xplane_udp::testing::FakeXPlane;
+
+mod common;
+
+use common::{connect, ALTITUDE};
+
+#[test]
+fn subscribe_confirmed_activates_known_dataref() {
+    let fake = FakeXPlane::start().unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    let mut session = connect(&fake);
+
+    session.subscribe_confirmed(ALTITUDE, 10, DataRefType::Float).unwrap();
+    assert_eq!(session.subscription_status(ALTITUDE), Some(SubscriptionStatus::Active));
+    assert_eq!(session.get_dataref(ALTITUDE), Some(DataRefValueType::Float(10000.0)));
+}
+
+#[test]
+fn subscribe_confirmed_times_out_for_unknown_dataref() {
+    let fake = FakeXPlane::start().unwrap();
+    let mut session = connect(&fake);
+    session.set_confirm_timeout(Duration::from_millis(200));
+
+    let error = session.subscribe_confirmed("sim/does/not/exist", 10, DataRefType::Float).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    assert_eq!(session.subscription_status("sim/does/not/exist"), Some(SubscriptionStatus::NoResponse));
+}
@@ -0,0 +1,65 @@
+//! Structs deriving `XPlaneDataRefs`, subscribed against [`FakeXPlane`].
+#![cfg(any(feature = "tokio", feature = "std"))]
+
+use xplane_udp::dataref_type::DataRefType;
+use xplane_udp::error::XPlaneError;
+use xplane_udp::group::DataRefGroup;
+use xplane_udp::session::Session;
+use xplane_udp::testing::FakeXPlane;
+use xplane_udp::XPlaneDataRefs;
+
+mod common;
+
+use common::{block_on, eventually, ALTITUDE, HEADING};
+
+const AP_ENGAGED: &str = "sim/cockpit2/autopilot/servos_on";
+const AP_MODE: &str = "sim/cockpit2/autopilot/autopilot_mode";
+
+#[derive(Debug, PartialEq, XPlaneDataRefs)]
+struct Autopilot {
+    #[dataref("sim/cockpit2/autopilot/altitude_dial_ft", freq = 5)]
+    altitude: f32,
+    #[dataref("sim/cockpit2/autopilot/heading_dial_deg_mag_pilot", freq = 2,)]
+    heading: f64,
+    #[dataref("sim/cockpit2/autopilot/servos_on", freq = 1)]
+    engaged: bool,
+    #[dataref("sim/cockpit2/autopilot/autopilot_mode", kind = DataRefType::Int, freq = 1)]
+    mode: String,
+}
+
+#[test]
+fn datarefs_follow_the_attributes() {
+    assert_eq!(Autopilot::datarefs(), vec![
+        (ALTITUDE, 5, DataRefType::Float),
+        (HEADING, 2, DataRefType::Double),
+        (AP_ENGAGED, 1, DataRefType::Bool),
+        (AP_MODE, 1, DataRefType::Int),
+    ]);
+}
+
+#[test]
+fn subscribes_and_reads_typed_fields() {
+    let fake = FakeXPlane::start().unwrap();
+    let address = fake.get_address().unwrap();
+    fake.set_value(ALTITUDE, 12000.0);
+    fake.set_value(HEADING, 270.0);
+    fake.set_value(AP_ENGAGED, 1.0);
+    fake.set_value(AP_MODE, 2.0);
+
+    block_on(async {
+        let mut session = Session::manual(address, address).await.unwrap();
+        session.run().await.unwrap();
+        assert!(matches!(Autopilot::read(&session), Err(XPlaneError::NotSubscribed(_))));
+
+        let results = Autopilot::subscribe_all(&mut session).await;
+        assert!(results.iter().all(|e| e.get_result().is_ok()), "{:?}", results);
+        assert!(eventually(|| Autopilot::read(&session).is_ok()));
+
+        assert_eq!(Autopilot::read(&session).unwrap(), Autopilot {
+            altitude: 12000.0,
+            heading: 270.0,
+            engaged: true,
+            mode: "2".to_string(),
+        });
+    });
+}
@@ -0,0 +1,20 @@
+//! History ring buffers of the blocking session against [`FakeXPlane`].
+#![cfg(any(feature = "tokio", feature = "std"))]
+
+use xplane_udp::dataref_type::DataRefType;
+use xplane_udp::testing::FakeXPlane;
+
+mod common;
+
+use common::{connect, eventually, ALTITUDE};
+
+#[test]
+fn subscribe_with_history_keeps_the_last_values() {
+    let fake = FakeXPlane::start().unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    let mut session = connect(&fake);
+
+    session.subscribe_with_history(ALTITUDE, 20, DataRefType::Float, 3).unwrap();
+    assert!(eventually(|| session.history(ALTITUDE).is_some_and(|e| e.len() == 3)));
+    assert!(session.history(ALTITUDE).unwrap().len() <= 3);
+}
@@ -0,0 +1,58 @@
+//! Tests of [`MockClient`] through the [`XPlaneClient`] trait.
+#![cfg(any(feature = "tokio", feature = "std"))]
+
+use std::io;
+
+use xplane_udp::client::XPlaneClient;
+use xplane_udp::dataref_type::{DataRefType, DataRefValueType};
+use xplane_udp::testing::MockClient;
+
+mod common;
+
+use common::block_on;
+
+const FLAPS: &str = "sim/cockpit2/controls/flap_ratio";
+
+/// Application code only knowing the trait
+async fn extend_flaps<C: XPlaneClient>(client: &mut C) -> io::Result<f32> {
+    let id = client.subscribe(FLAPS, 5, DataRefType::Float).await?;
+    let flaps: f32 = client.get(FLAPS).map_err(io::Error::from)?;
+    if flaps < 0.5 {
+        client.cmd("sim/flight_controls/flaps_down").await?;
+    }
+    client.release(id).await?;
+    Ok(flaps)
+}
+
+#[test]
+fn scripted_values_drive_application_code() {
+    let mut client = MockClient::new();
+    client.set_value(FLAPS, DataRefValueType::Float(0.25));
+
+    assert_eq!(block_on(extend_flaps(&mut client)).unwrap(), 0.25);
+    assert_eq!(client.get_commands(), vec!["sim/flight_controls/flaps_down".to_string()]);
+    assert!(!client.is_subscribed(FLAPS));
+}
+
+#[test]
+fn release_keeps_dataref_for_remaining_consumers() {
+    let mut client = MockClient::new();
+    let first = block_on(client.subscribe(FLAPS, 5, DataRefType::Float)).unwrap();
+    let second = block_on(client.subscribe(FLAPS, 10, DataRefType::Float)).unwrap();
+
+    block_on(client.release(first)).unwrap();
+    assert!(client.is_subscribed(FLAPS));
+    block_on(client.release(second)).unwrap();
+    assert!(!client.is_subscribed(FLAPS));
+    assert_eq!(block_on(client.release(second)).unwrap_err().kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn writes_are_recorded_and_applied() {
+    let mut client = MockClient::new();
+    block_on(client.subscribe(FLAPS, 5, DataRefType::Float)).unwrap();
+
+    block_on(client.set_dataref(FLAPS, 1.0)).unwrap();
+    assert_eq!(client.get_writes(), vec![(FLAPS.to_string(), 1.0)]);
+    assert_eq!(client.get_dataref(FLAPS), Some(DataRefValueType::Float(1.0)));
+}
@@ -0,0 +1,73 @@
+//! End-to-end tests of sessions against [`FakeXPlane`], from discovery to commands.
+#![cfg(any(feature = "tokio", feature = "std"))]
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::time::Duration;
+
+use xplane_udp::auto_discover::AutoDiscover;
+use xplane_udp::command_handler::AlertMessage;
+use xplane_udp::consts::{XP_DEFAULT_SENDING_PORT, XP_MULTICAST_GRP};
+use xplane_udp::dataref_type::{DataRefType, DataRefValueType};
+use xplane_udp::session;
+use xplane_udp::testing::FakeXPlane;
+
+mod common;
+
+use common::{block_on, connect, eventually, ALTITUDE, HEADING};
+
+#[test]
+fn release_many_keeps_other_consumers() {
+    let fake = FakeXPlane::start().unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    fake.set_value(HEADING, 90.0);
+    let mut session = connect(&fake);
+
+    let kept = session.subscribe(ALTITUDE, 5, DataRefType::Float).unwrap();
+    let released: Vec<_> = session.subscribe_many(&[(ALTITUDE, 5, DataRefType::Float), (HEADING, 5, DataRefType::Float)])
+        .into_iter()
+        .map(|e| e.into_result().unwrap())
+        .collect();
+
+    let results = session.release_many(&released);
+    assert!(results.iter().all(|e| e.is_ok()));
+    assert_eq!(results[1].get_dataref(), HEADING);
+    assert!(eventually(|| fake.get_subscriptions() == vec![(ALTITUDE.to_string(), 5)]));
+    session.release(kept).unwrap();
+}
+
+#[test]
+fn cmd_and_alert_are_sent() {
+    let fake = FakeXPlane::start().unwrap();
+    let session = connect(&fake);
+
+    session.cmd("sim/autopilot/servos_toggle").unwrap();
+    assert!(eventually(|| fake.get_commands() == vec!["sim/autopilot/servos_toggle".to_string()]));
+
+    let mut alert = AlertMessage::default();
+    alert.set_line("Gear", 0).unwrap();
+    alert.set_line("Down", 1).unwrap();
+    session.alert(alert.clone()).unwrap();
+    assert!(eventually(|| fake.get_alerts() == vec![alert.clone()]));
+}
+
+#[test]
+fn auto_discovered_session_subscribes() {
+    // A group port of its own, so a simulator on the network is not discovered instead
+    let group = SocketAddrV4::new(XP_MULTICAST_GRP, 49717);
+    let unspecified = |port| SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port).into();
+    let mut fake = FakeXPlane::start_split(unspecified(0), unspecified(XP_DEFAULT_SENDING_PORT)).unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    fake.emit_beacon(group, Duration::from_millis(50)).unwrap();
+
+    block_on(async {
+        let auto_discover = AutoDiscover::auto_discover(group, 2000).await.unwrap();
+        let mut session = session::Session::intercept_beacon(auto_discover).await.map_err(|(e, _)| e).unwrap();
+        let beacon = session.get_beacon().as_ref().unwrap().get_beacon().clone().unwrap();
+        assert_eq!(beacon.get_computer_name(), "FakeXPlane");
+        assert_eq!(beacon.get_port(), fake.get_address().unwrap().port());
+
+        session.run().await.unwrap();
+        session.subscribe_confirmed(ALTITUDE, 10, DataRefType::Float).await.unwrap();
+        assert_eq!(session.get_dataref(ALTITUDE), Some(DataRefValueType::Float(10000.0)));
+    });
+}
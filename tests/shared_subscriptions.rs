@@ -0,0 +1,46 @@
+//! Subscriptions shared between consumers, against [`FakeXPlane`].
+#![cfg(any(feature = "tokio", feature = "std"))]
+
+use std::io;
+
+use xplane_udp::dataref_type::DataRefType;
+use xplane_udp::testing::FakeXPlane;
+
+mod common;
+
+use common::{connect, eventually, ALTITUDE};
+
+#[test]
+fn release_renegotiates_frequency_until_last_consumer() {
+    let fake = FakeXPlane::start().unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    let mut session = connect(&fake);
+
+    let slow = session.subscribe(ALTITUDE, 5, DataRefType::Float).unwrap();
+    let fast = session.subscribe(ALTITUDE, 20, DataRefType::Float).unwrap();
+    assert!(eventually(|| fake.get_subscriptions() == vec![(ALTITUDE.to_string(), 20)]));
+
+    session.release(fast).unwrap();
+    assert!(eventually(|| fake.get_subscriptions() == vec![(ALTITUDE.to_string(), 5)]));
+    assert!(session.get_dataref(ALTITUDE).is_some());
+
+    session.release(slow).unwrap();
+    assert!(eventually(|| fake.get_subscriptions().is_empty()));
+    assert_eq!(session.get_dataref(ALTITUDE), None);
+    assert_eq!(session.release(slow).unwrap_err().kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn unsubscribe_drops_every_consumer() {
+    let fake = FakeXPlane::start().unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    let mut session = connect(&fake);
+
+    let first = session.subscribe(ALTITUDE, 5, DataRefType::Float).unwrap();
+    let second = session.subscribe(ALTITUDE, 10, DataRefType::Float).unwrap();
+    session.unsubscribe(ALTITUDE).unwrap();
+
+    assert!(eventually(|| fake.get_subscriptions().is_empty()));
+    assert_eq!(session.release(first).unwrap_err().kind(), io::ErrorKind::NotFound);
+    assert_eq!(session.release(second).unwrap_err().kind(), io::ErrorKind::NotFound);
+}
@@ -0,0 +1,82 @@
+//! Session teardown against [`FakeXPlane`].
+#![cfg(any(feature = "tokio", feature = "std"))]
+
+use std::future;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use xplane_udp::backend::{Backend, DefaultBackend};
+use xplane_udp::dataref_type::DataRefType;
+use xplane_udp::session::Session;
+use xplane_udp::testing::FakeXPlane;
+use xplane_udp::transport::Transport;
+
+mod common;
+
+use common::{block_on, connect, eventually, ALTITUDE, HEADING};
+
+/// UDP socket whose awaited sends never complete once stalled, non-blocking sends still go out
+struct StallingTransport {
+    inner: <DefaultBackend as Backend>::Socket,
+    stalled: Arc<AtomicBool>,
+}
+
+impl Transport for StallingTransport {
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        if self.stalled.load(Ordering::Relaxed) {
+            future::pending::<()>().await;
+        }
+        self.inner.send_to(buf, target).await
+    }
+
+    fn try_send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        self.inner.try_send_to(buf, target)
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.inner.recv_from(buf).await
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+}
+
+#[test]
+fn shutdown_timing_out_mid_send_still_unsubscribes() {
+    let fake = FakeXPlane::start().unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    let address = fake.get_address().unwrap();
+    let stalled = Arc::new(AtomicBool::new(false));
+
+    block_on(async {
+        let inner = DefaultBackend::bind(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0).into()).await.unwrap();
+        let transport = StallingTransport { inner, stalled: stalled.clone() };
+        let mut session = Session::with_transport(transport, address, address);
+        session.set_shutdown_timeout(Duration::from_millis(100));
+
+        session.subscribe(ALTITUDE, 10, DataRefType::Float).await.unwrap();
+        assert!(eventually(|| fake.get_subscriptions().len() == 1));
+
+        stalled.store(true, Ordering::Relaxed);
+        session.shutdown().await;
+    });
+    assert!(eventually(|| fake.get_subscriptions().is_empty()));
+}
+
+#[test]
+fn drop_unsubscribes_everything() {
+    let fake = FakeXPlane::start().unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    fake.set_value(HEADING, 90.0);
+    let mut session = connect(&fake);
+    session.subscribe(ALTITUDE, 10, DataRefType::Float).unwrap();
+    session.subscribe(HEADING, 10, DataRefType::Float).unwrap();
+    assert!(eventually(|| fake.get_subscriptions().len() == 2));
+
+    drop(session);
+    assert!(eventually(|| fake.get_subscriptions().is_empty()));
+}
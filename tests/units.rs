@@ -0,0 +1,24 @@
+//! Unit conversions of values sent by [`FakeXPlane`].
+#![cfg(any(feature = "tokio", feature = "std"))]
+
+use std::time::Duration;
+
+use xplane_udp::dataref_type::{DataRefType, DataRefValueType};
+use xplane_udp::testing::FakeXPlane;
+use xplane_udp::units::{Meters, Unit};
+
+mod common;
+
+use common::{connect, ALTITUDE};
+
+#[test]
+fn get_as_converts_int_datarefs() {
+    let fake = FakeXPlane::start().unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    let mut session = connect(&fake);
+
+    session.subscribe_with_unit(ALTITUDE, 10, DataRefType::Int, Unit::Feet).unwrap();
+    session.wait_for(ALTITUDE, |e| *e != DataRefValueType::Unknown, Duration::from_secs(2)).unwrap();
+    let meters = session.get_as::<Meters>(ALTITUDE).unwrap();
+    assert!((meters - 3048.0).abs() < 1e-6, "{}", meters);
+}
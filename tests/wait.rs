@@ -0,0 +1,43 @@
+//! Waiting for dataref values sent by [`FakeXPlane`].
+#![cfg(any(feature = "tokio", feature = "std"))]
+
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use xplane_udp::dataref_type::{DataRefType, DataRefValueType};
+use xplane_udp::testing::FakeXPlane;
+
+mod common;
+
+use common::{connect, ALTITUDE};
+
+#[test]
+fn wait_for_returns_matching_value() {
+    let fake = FakeXPlane::start().unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    let mut session = connect(&fake);
+    session.subscribe(ALTITUDE, 20, DataRefType::Float).unwrap();
+
+    let climbing = thread::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(100));
+            fake.set_value(ALTITUDE, 12000.0);
+        });
+        session.wait_for(ALTITUDE, |e| *e == DataRefValueType::Float(12000.0), Duration::from_secs(2))
+    });
+    assert_eq!(climbing.unwrap(), DataRefValueType::Float(12000.0));
+}
+
+#[test]
+fn wait_for_times_out() {
+    let fake = FakeXPlane::start().unwrap();
+    fake.set_value(ALTITUDE, 10000.0);
+    let mut session = connect(&fake);
+    session.subscribe(ALTITUDE, 20, DataRefType::Float).unwrap();
+
+    let start = Instant::now();
+    let error = session.wait_for(ALTITUDE, |_| false, Duration::from_millis(200)).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
@@ -0,0 +1,18 @@
+//! DREF writes of the blocking session against [`FakeXPlane`].
+#![cfg(any(feature = "tokio", feature = "std"))]
+
+use xplane_udp::testing::FakeXPlane;
+
+mod common;
+
+use common::{connect, eventually, HEADING};
+
+#[test]
+fn set_dataref_sends_dref() {
+    let fake = FakeXPlane::start().unwrap();
+    let session = connect(&fake);
+
+    session.set_dataref(HEADING, 270.5).unwrap();
+    assert!(eventually(|| fake.get_writes() == vec![(HEADING.to_string(), 270.5)]));
+    assert_eq!(fake.get_value(HEADING), Some(270.5));
+}
@@ -0,0 +1,199 @@
+//! `#[derive(XPlaneDataRefs)]` for `xplane_udp`, enabled there with the `derive` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, Ident, LitStr, Token};
+
+/// Maps a struct with named fields to a group of datarefs.
+///
+/// Every field needs `#[dataref("name", freq = 5)]`. The dataref type follows from the field type
+/// for `f32`, `f64`, `i32`, `u8`, `bool` and `char`, other types such as `String` or own enums
+/// need it spelled out with `kind = DataRefType::Int`. Fields are read through `FromDataRefValue`.
+///
+/// Implements `DataRefGroup` and adds `subscribe_all(&mut session)` and a frame-consistent
+/// `read(&session)` to the struct.
+#[proc_macro_derive(XPlaneDataRefs, attributes(dataref))]
+pub fn derive_xplane_datarefs(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+struct DataRefField {
+    ident: Ident,
+    ty: syn::Type,
+    name: LitStr,
+    freq: Expr,
+    kind: Option<Expr>,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new(Span::call_site(), "XPlaneDataRefs needs a struct with named fields")),
+        },
+        _ => return Err(syn::Error::new(Span::call_site(), "XPlaneDataRefs can only be derived for structs")),
+    };
+
+    let fields = fields.iter().map(parse_field).collect::<syn::Result<Vec<_>>>()?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let datarefs = fields.iter().map(|field| {
+        let DataRefField { ty, name, freq, kind, .. } = field;
+        let kind = match kind {
+            Some(kind) => quote!(#kind),
+            None => quote!(<#ty as ::xplane_udp::dataref_handle::DataRefScalar>::TYPE),
+        };
+        quote!((#name, #freq, #kind))
+    });
+
+    let reads = fields.iter().map(|field| {
+        let DataRefField { ident, ty, name, .. } = field;
+        quote! {
+            #ident: <#ty as ::xplane_udp::dataref_type::FromDataRefValue>::from_dataref(#name, snapshot.get(#name))?
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::xplane_udp::group::DataRefGroup for #ident #ty_generics #where_clause {
+            fn datarefs() -> ::std::vec::Vec<(&'static str, i32, ::xplane_udp::dataref_type::DataRefType)> {
+                ::std::vec![#(#datarefs),*]
+            }
+
+            fn from_snapshot(snapshot: &::xplane_udp::snapshot::Snapshot)
+                -> ::std::result::Result<Self, ::xplane_udp::error::XPlaneError> {
+                ::std::result::Result::Ok(Self { #(#reads),* })
+            }
+        }
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Subscribes every dataref of the struct
+            pub async fn subscribe_all<B, T>(session: &mut ::xplane_udp::session::Session<B, T>)
+                -> ::std::vec::Vec<::xplane_udp::dataref_handler::BatchResult<::xplane_udp::dataref::SubscriptionId>>
+            where
+                B: ::xplane_udp::backend::Backend,
+                T: ::xplane_udp::transport::Transport,
+            {
+                session.subscribe_group::<Self>().await
+            }
+
+            /// Reads all fields from the same RREF message
+            pub fn read<B, T>(session: &::xplane_udp::session::Session<B, T>)
+                -> ::std::result::Result<Self, ::xplane_udp::error::XPlaneError>
+            where
+                B: ::xplane_udp::backend::Backend,
+                T: ::xplane_udp::transport::Transport,
+            {
+                session.read_group::<Self>()
+            }
+        }
+    })
+}
+
+fn parse_field(field: &syn::Field) -> syn::Result<DataRefField> {
+    let ident = field.ident.clone().expect("named field");
+    let attr = field.attrs.iter()
+        .find(|attr| attr.path().is_ident("dataref"))
+        .ok_or_else(|| syn::Error::new_spanned(field, "missing #[dataref(\"name\", freq = ...)] attribute"))?;
+
+    attr.parse_args_with(|input: ParseStream| {
+        let name: LitStr = input.parse()?;
+        let mut freq = None;
+        let mut kind = None;
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let value: Expr = input.parse()?;
+            match key.to_string().as_str() {
+                "freq" if freq.is_none() => freq = Some(value),
+                "kind" if kind.is_none() => kind = Some(value),
+                "freq" | "kind" => return Err(syn::Error::new_spanned(key, "duplicate argument")),
+                _ => return Err(syn::Error::new_spanned(key, "expected `freq` or `kind`")),
+            }
+        }
+
+        let freq = freq.ok_or_else(|| syn::Error::new(input.span(), "missing `freq = ...`"))?;
+        Ok(DataRefField { ident, ty: field.ty.clone(), name, freq, kind })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn error_of(input: DeriveInput) -> String {
+        expand(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn expands_attributes_in_any_order() {
+        let input: DeriveInput = parse_quote! {
+            struct Autopilot {
+                #[dataref("sim/a", freq = 5)]
+                a: f32,
+                #[dataref("sim/b", kind = DataRefType::Int, freq = 1,)]
+                b: String,
+            }
+        };
+        assert!(expand(input).is_ok());
+    }
+
+    #[test]
+    fn missing_freq() {
+        let input = parse_quote! {
+            struct Autopilot {
+                #[dataref("sim/a", kind = DataRefType::Int)]
+                a: i32,
+            }
+        };
+        assert_eq!(error_of(input), "missing `freq = ...`");
+    }
+
+    #[test]
+    fn unknown_argument() {
+        let input = parse_quote! {
+            struct Autopilot {
+                #[dataref("sim/a", freq = 5, frequency = 5)]
+                a: f32,
+            }
+        };
+        assert_eq!(error_of(input), "expected `freq` or `kind`");
+    }
+
+    #[test]
+    fn duplicate_argument() {
+        let input = parse_quote! {
+            struct Autopilot {
+                #[dataref("sim/a", freq = 5, freq = 10)]
+                a: f32,
+            }
+        };
+        assert_eq!(error_of(input), "duplicate argument");
+    }
+
+    #[test]
+    fn missing_attribute() {
+        let input = parse_quote! {
+            struct Autopilot {
+                a: f32,
+            }
+        };
+        assert_eq!(error_of(input), "missing #[dataref(\"name\", freq = ...)] attribute");
+    }
+
+    #[test]
+    fn only_structs_with_named_fields() {
+        assert_eq!(error_of(parse_quote!(struct Autopilot(f32);)), "XPlaneDataRefs needs a struct with named fields");
+        assert_eq!(error_of(parse_quote!(enum Autopilot { On })), "XPlaneDataRefs can only be derived for structs");
+    }
+}